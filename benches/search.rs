@@ -0,0 +1,104 @@
+//! Query time of `search` on a synthetic store, with and without the search index.
+//!
+//! Run with `cargo bench --bench search`; set `BENCH_ITEMS` to change the store size
+//! (default 100000).
+
+use rs_cli_tmpl::FilesystemStorage;
+use rs_cli_tmpl::SearchMode;
+use rs_cli_tmpl::SilentReporter;
+use rs_cli_tmpl::commands;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const VOCABULARY: usize = 5_000;
+const WORDS_PER_ITEM: usize = 12;
+const RUNS: u32 = 5;
+
+/// Small xorshift generator so every run builds the same store.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+fn vocabulary(rng: &mut Rng) -> Vec<String> {
+    (0..VOCABULARY)
+        .map(|_| {
+            let len = 4 + rng.below(6);
+            (0..len).map(|_| (b'a' + rng.below(26) as u8) as char).collect()
+        })
+        .collect()
+}
+
+/// Write items straight to disk; the storage derives their metadata on first read.
+fn populate(root: &Path, items: usize, words: &[String], rng: &mut Rng) {
+    for n in 0..items {
+        let dir = root.join(format!("item-{n:06}"));
+        fs::create_dir_all(&dir).unwrap();
+        let content: Vec<&str> =
+            (0..WORDS_PER_ITEM).map(|_| words[rng.below(words.len())].as_str()).collect();
+        fs::write(dir.join("item.txt"), content.join(" ")).unwrap();
+    }
+}
+
+fn time<T>(mut run: impl FnMut() -> T) -> (Duration, T) {
+    let mut result = run();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        result = run();
+    }
+    (start.elapsed() / RUNS, result)
+}
+
+fn main() {
+    let items = std::env::var("BENCH_ITEMS").ok().and_then(|n| n.parse().ok()).unwrap_or(100_000);
+    let dir = TempDir::new().unwrap();
+    let storage = FilesystemStorage::new(dir.path());
+    let mut rng = Rng(0x5eed);
+    let words = vocabulary(&mut rng);
+
+    let start = Instant::now();
+    populate(dir.path(), items, &words, &mut rng);
+    println!("populated {items} items in {:.2?}", start.elapsed());
+
+    let queries = [
+        ("common word", words[0].clone()),
+        ("two words", format!("{} {}", words[1], words[2])),
+        ("word prefix", words[3][..4].to_string()),
+        ("no match", "qqqzzz".to_string()),
+    ];
+    let search = |query: &str| {
+        commands::search(&storage, &SilentReporter, query, SearchMode::default()).unwrap().len()
+    };
+
+    let scans: Vec<_> = queries.iter().map(|(_, query)| time(|| search(query))).collect();
+
+    let start = Instant::now();
+    let report = storage.rebuild_index().unwrap();
+    println!(
+        "built index of {} terms in {:.2?}\n\n{:<12} {:>8} {:>12} {:>12}",
+        report.terms,
+        start.elapsed(),
+        "query",
+        "matches",
+        "scan",
+        "indexed"
+    );
+
+    for ((label, query), (scan, expected)) in queries.iter().zip(scans) {
+        let (indexed, found) = time(|| search(query));
+        assert_eq!(found, expected, "index changed the results for {query:?}");
+        println!("{label:<12} {found:>8} {scan:>12.2?} {indexed:>12.2?}");
+    }
+}
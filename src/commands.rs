@@ -1,41 +1,594 @@
-use crate::core::{Execute, add_item::AddItem, delete_item::DeleteItem, list_items::ListItems};
+use crate::config::{self, Config, KEYS, Settings};
+use crate::core::{
+    Execute,
+    add_item::AddItem,
+    append_item::AppendItem,
+    browse_items::{BrowseEntry, BrowseItems},
+    delete_item::DeleteItem,
+    describe_item::DescribeItem,
+    empty_trash::EmptyTrash,
+    export_item::ExportItem,
+    find_case_collisions::FindCaseCollisions,
+    get_item::GetItem,
+    import_item::ImportItem,
+    list_trash::ListTrash,
+    list_versions::ListVersions,
+    restore_item::RestoreItem,
+    rollback_item::RollbackItem,
+    search_items::SearchItems,
+    tag_item::TagItem,
+    untag_item::UntagItem,
+    update_item::UpdateItem,
+};
 use crate::error::AppError;
-use crate::storage::FilesystemStorage;
+use crate::id::ItemId;
+use crate::index::IndexReport;
+use crate::input;
+use crate::logfile::{CompactReport, LogFileStorage};
+use crate::metadata::{ItemMetadata, TrashEntry, is_binary};
+use crate::output::{ConfigEntry, Event, Finding, ListEntry, Reporter};
+use crate::search::{SearchMatch, SearchMode};
+use crate::storage::{FilesystemStorage, Storage};
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-/// Add a new item to storage using the default filesystem backend.
-pub fn add(id: &str, content: &str) -> Result<(), AppError> {
-    let storage = FilesystemStorage::new_default()?;
-    let command = AddItem { id, content };
+/// Options accepted by [`add`].
+#[derive(Debug, Clone, Default)]
+pub struct AddOptions {
+    /// Overwrite the item if it already exists instead of failing.
+    pub force: bool,
+    /// Media type recorded in the item's metadata.
+    pub content_type: Option<String>,
+    /// Tags attached to the item; with `force` they replace the tags of the item overwritten.
+    pub tags: Vec<String>,
+}
+
+/// Options accepted by [`list`].
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Include size, update time and content type for each identifier.
+    pub long: bool,
+    /// Only list items carrying every one of these tags.
+    pub tags: Vec<String>,
+    /// Accept items carrying any of `tags` instead of all of them.
+    pub any_tag: bool,
+    /// Only list entries inside this namespace, such as `team/service`.
+    pub prefix: Option<String>,
+    /// List every item below the namespace instead of folding nested namespaces.
+    pub recursive: bool,
+}
+
+/// Options accepted by [`show`].
+#[derive(Debug, Clone, Default)]
+pub struct ShowOptions {
+    /// Write binary content even when the reporter targets an interactive terminal.
+    pub raw: bool,
+    /// Print this retained revision instead of the current content.
+    pub version: Option<u64>,
+}
+
+/// Options accepted by [`delete`].
+#[derive(Debug, Clone, Default)]
+pub struct DeleteOptions {
+    /// Remove the item permanently instead of moving it to the trash.
+    pub purge: bool,
+    /// Treat the id as a namespace and delete every item below it.
+    pub recursive: bool,
+}
+
+/// Add a new item to the storage.
+pub fn add(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    content: impl AsRef<[u8]>,
+    options: &AddOptions,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = AddItem {
+        id,
+        content: content.as_ref(),
+        content_type: options.content_type.as_deref(),
+        tags: &options.tags,
+        force: options.force,
+    };
 
-    command.execute(&storage)?;
-    println!("✅ Added item '{id}'");
+    command.execute(storage)?;
+    reporter.report(&Event::ItemAdded { id: id.to_string() });
     Ok(())
 }
 
-/// List all stored item identifiers.
-pub fn list() -> Result<Vec<String>, AppError> {
-    let storage = FilesystemStorage::new_default()?;
-    let command = ListItems;
-    let items = command.execute(&storage)?;
+/// Add a new item by streaming its content from `reader`, without buffering it in memory.
+pub fn add_from_reader(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    reader: &mut dyn Read,
+    options: &AddOptions,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = ImportItem {
+        id,
+        reader: RefCell::new(reader),
+        content_type: options.content_type.as_deref(),
+        tags: &options.tags,
+        force: options.force,
+    };
 
-    println!("📦 Stored items:");
-    if items.is_empty() {
-        println!("(none)");
-    } else {
-        for id in &items {
-            println!("- {id}");
+    command.execute(storage)?;
+    reporter.report(&Event::ItemAdded { id: id.to_string() });
+    Ok(())
+}
+
+/// Replace the content of an existing item.
+pub fn update(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    content: impl AsRef<[u8]>,
+    content_type: Option<&str>,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = UpdateItem { id, content: content.as_ref(), content_type };
+
+    command.execute(storage)?;
+    reporter.report(&Event::ItemUpdated { id: id.to_string() });
+    Ok(())
+}
+
+/// Append content to the end of an existing item.
+pub fn append(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    content: impl AsRef<[u8]>,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = AppendItem { id, content: content.as_ref() };
+
+    command.execute(storage)?;
+    reporter.report(&Event::ItemAppended { id: id.to_string() });
+    Ok(())
+}
+
+/// Open an item's content in `editor` and save the result back.
+///
+/// Binary items are refused since a text editor would corrupt them.
+pub fn edit(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    editor: &str,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let original = GetItem { id }.execute(storage)?;
+    let original = match String::from_utf8(original) {
+        Ok(text) if !is_binary(text.as_bytes()) => text,
+        _ => {
+            return Err(AppError::EditorFailed(format!(
+                "item '{id}' contains binary data and cannot be edited as text"
+            )));
+        }
+    };
+    let edited = input::edit_text(editor, &original)?;
+
+    if edited == original {
+        reporter.report(&Event::ItemUnchanged { id: id.to_string() });
+        return Ok(());
+    }
+
+    UpdateItem { id, content: edited.as_bytes(), content_type: None }.execute(storage)?;
+    reporter.report(&Event::ItemUpdated { id: id.to_string() });
+    Ok(())
+}
+
+/// Print an item's raw content so it can be piped to other tools.
+///
+/// Content is streamed into the reporter's [`content_sink`](Reporter::content_sink) when it
+/// has one. Binary content is refused when the reporter writes to a terminal unless
+/// [`ShowOptions::raw`] is set; streamed content is judged by its first 8 KiB. Returns the
+/// number of bytes written.
+pub fn show(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    options: &ShowOptions,
+) -> Result<u64, AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let guard_terminal = !options.raw && reporter.is_terminal();
+
+    if let Some(mut sink) = reporter.content_sink() {
+        let written = if guard_terminal {
+            let mut guard = TextGuard::new(&mut sink);
+            let result =
+                ExportItem { id, version: options.version, writer: RefCell::new(&mut guard) }
+                    .execute(storage)
+                    .and_then(|written| guard.finish().map(|_| written).map_err(AppError::from));
+            if guard.rejected {
+                return Err(AppError::BinaryContent(id.to_string()));
+            }
+            result?
+        } else {
+            ExportItem { id, version: options.version, writer: RefCell::new(&mut sink) }
+                .execute(storage)?
+        };
+        sink.flush()?;
+        return Ok(written);
+    }
+
+    let mut content = Vec::new();
+    let written = ExportItem { id, version: options.version, writer: RefCell::new(&mut content) }
+        .execute(storage)?;
+    if guard_terminal && is_binary(&content) {
+        return Err(AppError::BinaryContent(id.to_string()));
+    }
+
+    reporter.report(&Event::ItemContent { id: id.to_string(), content });
+    Ok(written)
+}
+
+/// Print the retained revisions of an item, oldest first.
+pub fn history(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+) -> Result<Vec<ItemMetadata>, AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = ListVersions { id };
+    let versions = command.execute(storage)?;
+
+    reporter.report(&Event::ItemHistory { id: id.to_string(), versions: versions.clone() });
+    Ok(versions)
+}
+
+/// Restore a retained revision of an item as its newest revision.
+pub fn rollback(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    version: u64,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = RollbackItem { id, version };
+
+    command.execute(storage)?;
+    reporter.report(&Event::ItemRolledBack { id: id.to_string(), version });
+    Ok(())
+}
+
+/// Number of leading bytes [`TextGuard`] inspects before letting output through.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Writer that holds back the start of a stream until it is known not to be binary.
+struct TextGuard<W> {
+    inner: W,
+    pending: Option<Vec<u8>>,
+    rejected: bool,
+}
+
+impl<W: Write> TextGuard<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, pending: Some(Vec::new()), rejected: false }
+    }
+
+    /// Release whatever is still held back once the stream has ended.
+    fn finish(&mut self) -> io::Result<()> {
+        self.release(true)
+    }
+
+    fn release(&mut self, complete: bool) -> io::Result<()> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        let binary = if complete {
+            is_binary(&pending)
+        } else {
+            // A multi-byte character may be cut off at the end of the sniffed prefix.
+            pending.contains(&0)
+                || matches!(std::str::from_utf8(&pending), Err(err) if err.error_len().is_some())
+        };
+        if binary {
+            self.rejected = true;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "binary content"));
+        }
+        self.inner.write_all(&pending)
+    }
+}
+
+impl<W: Write> Write for TextGuard<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(pending) = self.pending.as_mut() else {
+            return self.inner.write(buf);
+        };
+        pending.extend_from_slice(buf);
+        if pending.len() >= SNIFF_LEN {
+            self.release(false)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// List stored item identifiers, folding nested namespaces into `<namespace>/` entries unless
+/// `options.recursive` is set.
+pub fn list(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    options: &ListOptions,
+) -> Result<Vec<String>, AppError> {
+    let command = BrowseItems {
+        prefix: options.prefix.as_deref(),
+        recursive: options.recursive,
+        tags: &options.tags,
+        match_any: options.any_tag,
+    };
+    let items = command.execute(storage)?;
+
+    let mut entries = Vec::with_capacity(items.len());
+    for item in &items {
+        let metadata = match item {
+            BrowseEntry::Item(id) if options.long => Some(DescribeItem { id }.execute(storage)?),
+            _ => None,
+        };
+        entries.push(ListEntry { id: item.as_str().to_string(), metadata });
+    }
+
+    reporter.report(&Event::Listing { items: entries });
+    Ok(items.iter().map(|item| item.as_str().to_string()).collect())
+}
+
+/// Delete an item, moving it to the trash unless `options.purge` is set.
+///
+/// With `options.recursive`, `id` names a namespace and every item below it is deleted.
+pub fn delete(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    options: &DeleteOptions,
+) -> Result<(), AppError> {
+    let ids = if options.recursive {
+        // The namespace must be a valid id itself, so an empty or root prefix cannot reach the
+        // whole store.
+        let prefix = storage.id_rules().parse(id.strip_suffix('/').unwrap_or(id))?;
+        let command = BrowseItems {
+            prefix: Some(prefix.as_str()),
+            recursive: true,
+            tags: &[],
+            match_any: false,
+        };
+        let ids: Vec<ItemId> = command
+            .execute(storage)?
+            .into_iter()
+            .filter_map(|entry| match entry {
+                BrowseEntry::Item(id) => Some(id),
+                BrowseEntry::Namespace(_) => None,
+            })
+            .collect();
+        if ids.is_empty() {
+            return Err(AppError::ItemNotFound(id.to_string()));
         }
+        ids
+    } else {
+        vec![storage.id_rules().parse(id)?]
+    };
+
+    for id in ids {
+        DeleteItem { id: &id, purge: options.purge }.execute(storage)?;
+        let id = id.to_string();
+        reporter.report(&if options.purge {
+            Event::ItemDeleted { id }
+        } else {
+            Event::ItemTrashed { id }
+        });
     }
+    Ok(())
+}
 
-    Ok(items)
+/// Find the lines of stored items matching `query`.
+pub fn search(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    query: &str,
+    mode: SearchMode,
+) -> Result<Vec<SearchMatch>, AppError> {
+    let command = SearchItems { query, mode };
+    let matches = command.execute(storage)?;
+
+    reporter.report(&Event::SearchResults { query: query.to_string(), matches: matches.clone() });
+    Ok(matches)
 }
 
-/// Delete an item from storage.
-pub fn delete(id: &str) -> Result<(), AppError> {
-    let storage = FilesystemStorage::new_default()?;
-    let command = DeleteItem { id };
+/// Build the search index from scratch, enabling it for the store if needed.
+pub fn index_rebuild(
+    storage: &FilesystemStorage,
+    reporter: &impl Reporter,
+) -> Result<IndexReport, AppError> {
+    let report = storage.rebuild_index()?;
+
+    reporter.report(&Event::IndexRebuilt(report));
+    Ok(report)
+}
+
+/// Check that the search index matches item content, failing with `IndexOutOfDate` otherwise.
+pub fn index_verify(
+    storage: &FilesystemStorage,
+    reporter: &impl Reporter,
+) -> Result<IndexReport, AppError> {
+    let report = storage.verify_index()?;
+    if report.missing > 0 || report.stale > 0 {
+        return Err(AppError::IndexOutOfDate(report.missing, report.stale));
+    }
 
-    command.execute(&storage)?;
-    println!("🗑️  Deleted item '{id}'");
+    reporter.report(&Event::IndexVerified(report));
+    Ok(report)
+}
+
+/// Rewrite the store log without the records that no longer describe the store.
+pub fn compact(
+    storage: &LogFileStorage,
+    reporter: &impl Reporter,
+) -> Result<CompactReport, AppError> {
+    let report = storage.compact()?;
+
+    reporter.report(&Event::StoreCompacted(report));
+    Ok(report)
+}
+
+/// Check the store for problems, reporting every one found and then failing with
+/// `ProblemsFound` if there were any.
+///
+/// Names differing only in letter case are reported, since they would share one directory on
+/// case-insensitive filesystems.
+pub fn doctor(storage: &impl Storage, reporter: &impl Reporter) -> Result<Vec<Finding>, AppError> {
+    let findings: Vec<Finding> = FindCaseCollisions
+        .execute(storage)?
+        .into_iter()
+        .map(|ids| Finding::CaseCollision { ids })
+        .collect();
+
+    reporter.report(&Event::DoctorReport { findings: findings.clone() });
+    if !findings.is_empty() {
+        return Err(AppError::ProblemsFound(findings.len()));
+    }
+    Ok(findings)
+}
+
+/// Attach a tag to an existing item.
+pub fn tag_add(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    tag: &str,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = TagItem { id, tag };
+
+    let id = id.to_string();
+    if command.execute(storage)? {
+        reporter.report(&Event::ItemTagged { id, tag: tag.to_string() });
+    } else {
+        reporter.report(&Event::ItemUnchanged { id });
+    }
     Ok(())
 }
+
+/// Detach a tag from an existing item.
+pub fn tag_remove(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    id: &str,
+    tag: &str,
+) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = UntagItem { id, tag };
+
+    let id = id.to_string();
+    if command.execute(storage)? {
+        reporter.report(&Event::ItemUntagged { id, tag: tag.to_string() });
+    } else {
+        reporter.report(&Event::ItemUnchanged { id });
+    }
+    Ok(())
+}
+
+/// List deleted items still held in the trash.
+pub fn trash_list(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+) -> Result<Vec<TrashEntry>, AppError> {
+    let command = ListTrash;
+    let entries = command.execute(storage)?;
+
+    reporter.report(&Event::TrashListing { entries: entries.clone() });
+    Ok(entries)
+}
+
+/// Bring the most recently deleted copy of an item back from the trash.
+pub fn restore(storage: &impl Storage, reporter: &impl Reporter, id: &str) -> Result<(), AppError> {
+    let id = &storage.id_rules().parse(id)?;
+    let command = RestoreItem { id };
+
+    command.execute(storage)?;
+    reporter.report(&Event::ItemRestored { id: id.to_string() });
+    Ok(())
+}
+
+/// Permanently remove trashed items, optionally only those deleted at least `older_than` ago.
+pub fn trash_empty(
+    storage: &impl Storage,
+    reporter: &impl Reporter,
+    older_than: Option<Duration>,
+) -> Result<Vec<TrashEntry>, AppError> {
+    let command = EmptyTrash { older_than };
+    let removed = command.execute(storage)?;
+
+    reporter.report(&Event::TrashEmptied { entries: removed.clone() });
+    Ok(removed)
+}
+
+/// Print the effective value of a configuration key.
+#[doc(hidden)]
+pub fn config_get(
+    config: &Config,
+    reporter: &impl Reporter,
+    key: &str,
+) -> Result<Option<String>, AppError> {
+    let (value, source) = config.get(key)?;
+
+    reporter.report(&Event::ConfigValue(ConfigEntry {
+        key: key.to_string(),
+        value: value.clone(),
+        source,
+    }));
+    Ok(value)
+}
+
+/// Persist a configuration value in the config file at `path`.
+#[doc(hidden)]
+pub fn config_set(
+    reporter: &impl Reporter,
+    path: &Path,
+    key: &str,
+    value: &str,
+) -> Result<(), AppError> {
+    let mut settings = Settings::load(path)?;
+    settings.set(key, value)?;
+    settings.save(path)?;
+
+    reporter.report(&Event::ConfigSet {
+        key: key.to_string(),
+        value: value.to_string(),
+        path: path.display().to_string(),
+    });
+    Ok(())
+}
+
+/// Print every configuration key with its effective value and source.
+#[doc(hidden)]
+pub fn config_list(
+    config: &Config,
+    reporter: &impl Reporter,
+) -> Result<Vec<ConfigEntry>, AppError> {
+    let mut entries = Vec::with_capacity(KEYS.len());
+    for key in KEYS {
+        let (value, source) = config.get(key)?;
+        entries.push(ConfigEntry { key: key.to_string(), value, source });
+    }
+
+    reporter.report(&Event::ConfigListing { entries: entries.clone() });
+    Ok(entries)
+}
+
+/// Print the location of the config file.
+#[doc(hidden)]
+pub fn config_path(reporter: &impl Reporter) -> Result<PathBuf, AppError> {
+    let path = config::config_path()?;
+
+    reporter.report(&Event::ConfigPath { path: path.display().to_string() });
+    Ok(path)
+}
@@ -0,0 +1,595 @@
+//! Layered configuration.
+//!
+//! Settings are merged from four layers, highest precedence first:
+//!
+//! 1. command-line flags (`--format`, `--store`, `--backend`, `--color`)
+//! 2. environment variables (`RS_CLI_TMPL_FORMAT`, `RS_CLI_TMPL_HOME`, `RS_CLI_TMPL_EDITOR`,
+//!    `VISUAL`, `EDITOR`, `RS_CLI_TMPL_CONFIRM`, `RS_CLI_TMPL_COLOR`, `NO_COLOR`,
+//!    `RS_CLI_TMPL_LOCK_TIMEOUT`, `RS_CLI_TMPL_HISTORY_LIMIT`, `RS_CLI_TMPL_ID_CHARSET`,
+//!    `RS_CLI_TMPL_ID_MAX_LENGTH`, `RS_CLI_TMPL_ID_FOLD_CASE`, `RS_CLI_TMPL_BACKEND`)
+//! 3. the TOML config file (see [`config_path`])
+//! 4. built-in defaults
+
+use crate::error::AppError;
+use crate::id::{DEFAULT_MAX_ID_LENGTH, IdCharset, IdRules};
+use crate::output::OutputFormat;
+use crate::storage::{
+    DEFAULT_HISTORY_LIMIT, DEFAULT_LOCK_TIMEOUT, FilesystemStorage, STORE_ENV_VAR,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable pointing at an explicit config file.
+pub const CONFIG_ENV_VAR: &str = "RS_CLI_TMPL_CONFIG";
+
+pub(crate) const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Keys accepted by `config get/set` and the config file.
+pub const KEYS: [&str; 11] = [
+    "format",
+    "store",
+    "editor",
+    "confirm",
+    "color",
+    "lock_timeout",
+    "history_limit",
+    "id_charset",
+    "id_max_length",
+    "id_fold_case",
+    "backend",
+];
+
+/// When to emit ANSI colors in human output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Color stdout and stderr each only when it is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Storage backend holding the items of the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// One directory per item below the storage root.
+    #[default]
+    Filesystem,
+    /// A single SQLite database in the storage root; needs the `sqlite` cargo feature.
+    Sqlite,
+    /// A single append-only log file in the storage root.
+    Logfile,
+}
+
+/// A partial set of settings, as supplied by a single layer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Default output format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    /// Storage root directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<PathBuf>,
+    /// Editor command used to compose item content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Ask before destructive operations such as `delete`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<bool>,
+    /// ANSI color mode for human output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorMode>,
+    /// How long to wait for storage locks held by other processes, e.g. `"30s"`.
+    #[serde(skip_serializing_if = "Option::is_none", with = "duration_text")]
+    pub lock_timeout: Option<Duration>,
+    /// Number of prior revisions kept per item; `0` disables history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_limit: Option<usize>,
+    /// Letters and digits accepted in item identifiers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_charset: Option<IdCharset>,
+    /// Longest accepted item identifier in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_max_length: Option<usize>,
+    /// Lowercase item identifiers so spellings differing only in case name the same item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_fold_case: Option<bool>,
+    /// Storage backend holding the items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<Backend>,
+}
+
+/// Layer a setting's effective value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Flag,
+    Env,
+    File,
+    Default,
+}
+
+/// Effective configuration merged from every layer.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    flags: Settings,
+    env: Settings,
+    file: Settings,
+}
+
+impl Settings {
+    /// Read a settings file, treating a missing file as empty.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| {
+                AppError::config_error(format!("invalid config file {}: {err}", path.display()))
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write the settings as TOML, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let text = toml::to_string_pretty(self)
+            .map_err(|err| AppError::config_error(format!("failed to encode config: {err}")))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Collect settings from environment variables.
+    pub fn from_env() -> Result<Self, AppError> {
+        Self::from_lookup(|name| env::var_os(name))
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<OsString>) -> Result<Self, AppError> {
+        let var = |name: &str| {
+            lookup(name).filter(|value| !value.is_empty()).map(|value| {
+                value
+                    .into_string()
+                    .map_err(|_| AppError::config_error(format!("{name} is not valid UTF-8")))
+            })
+        };
+
+        let mut settings = Self::default();
+        if let Some(value) = var("RS_CLI_TMPL_FORMAT") {
+            settings.set("format", &value?)?;
+        }
+        if let Some(value) = lookup(STORE_ENV_VAR).filter(|value| !value.is_empty()) {
+            settings.store = Some(PathBuf::from(value));
+        }
+        let editor = ["RS_CLI_TMPL_EDITOR", "VISUAL", "EDITOR"].into_iter().find_map(var);
+        if let Some(value) = editor {
+            settings.editor = Some(value?);
+        }
+        if let Some(value) = var("RS_CLI_TMPL_CONFIRM") {
+            settings.set("confirm", &value?)?;
+        }
+        if var("NO_COLOR").is_some() {
+            settings.color = Some(ColorMode::Never);
+        }
+        if let Some(value) = var("RS_CLI_TMPL_COLOR") {
+            settings.set("color", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_LOCK_TIMEOUT") {
+            settings.set("lock_timeout", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_HISTORY_LIMIT") {
+            settings.set("history_limit", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_ID_CHARSET") {
+            settings.set("id_charset", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_ID_MAX_LENGTH") {
+            settings.set("id_max_length", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_ID_FOLD_CASE") {
+            settings.set("id_fold_case", &value?)?;
+        }
+        if let Some(value) = var("RS_CLI_TMPL_BACKEND") {
+            settings.set("backend", &value?)?;
+        }
+        Ok(settings)
+    }
+
+    /// Current value of `key` rendered as a string, if set in this layer.
+    pub fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(match validate_key(key)? {
+            "format" => self.format.map(|format| enum_name(&format)),
+            "store" => self.store.as_ref().map(|path| path.display().to_string()),
+            "editor" => self.editor.clone(),
+            "confirm" => self.confirm.map(|confirm| confirm.to_string()),
+            "color" => self.color.map(|color| enum_name(&color)),
+            "lock_timeout" => self.lock_timeout.map(format_duration),
+            "history_limit" => self.history_limit.map(|limit| limit.to_string()),
+            "id_charset" => self.id_charset.map(|charset| enum_name(&charset)),
+            "id_max_length" => self.id_max_length.map(|length| length.to_string()),
+            "id_fold_case" => self.id_fold_case.map(|fold| fold.to_string()),
+            _ => self.backend.map(|backend| enum_name(&backend)),
+        })
+    }
+
+    /// Parse `value` and assign it to `key`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), AppError> {
+        match validate_key(key)? {
+            "format" => self.format = Some(parse_enum(key, value)?),
+            "store" => self.store = Some(PathBuf::from(value)),
+            "editor" => self.editor = Some(value.to_string()),
+            "confirm" => self.confirm = Some(parse_bool(key, value)?),
+            "color" => self.color = Some(parse_enum(key, value)?),
+            "lock_timeout" => self.lock_timeout = Some(parse_duration(key, value)?),
+            "history_limit" => self.history_limit = Some(parse_count(key, value)?),
+            "id_charset" => self.id_charset = Some(parse_enum(key, value)?),
+            "id_max_length" => self.id_max_length = Some(parse_positive(key, value)?),
+            "id_fold_case" => self.id_fold_case = Some(parse_bool(key, value)?),
+            _ => self.backend = Some(parse_enum(key, value)?),
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Merge command-line flags with the environment and the config file.
+    pub fn load(flags: Settings) -> Result<Self, AppError> {
+        let file = Settings::load(&config_path()?)?;
+        Ok(Self { flags, env: Settings::from_env()?, file })
+    }
+
+    /// Build a configuration from explicit layers.
+    pub fn from_layers(flags: Settings, env: Settings, file: Settings) -> Self {
+        Self { flags, env, file }
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.layers().find_map(|(_, settings)| settings.format).unwrap_or_default()
+    }
+
+    /// Storage root from the layers, falling back to [`FilesystemStorage::default_root`].
+    pub fn store(&self) -> Result<PathBuf, AppError> {
+        match self.layers().find_map(|(_, settings)| settings.store.clone()) {
+            Some(root) => Ok(root),
+            None => FilesystemStorage::default_root(),
+        }
+    }
+
+    pub fn editor(&self) -> Option<String> {
+        self.layers().find_map(|(_, settings)| settings.editor.clone())
+    }
+
+    pub fn confirm(&self) -> bool {
+        self.layers().find_map(|(_, settings)| settings.confirm).unwrap_or(false)
+    }
+
+    pub fn color(&self) -> ColorMode {
+        self.layers().find_map(|(_, settings)| settings.color).unwrap_or_default()
+    }
+
+    pub fn lock_timeout(&self) -> Duration {
+        self.layers()
+            .find_map(|(_, settings)| settings.lock_timeout)
+            .unwrap_or(DEFAULT_LOCK_TIMEOUT)
+    }
+
+    pub fn history_limit(&self) -> usize {
+        self.layers()
+            .find_map(|(_, settings)| settings.history_limit)
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.layers().find_map(|(_, settings)| settings.backend).unwrap_or_default()
+    }
+
+    /// Rules item identifiers are checked against.
+    pub fn id_rules(&self) -> IdRules {
+        IdRules {
+            charset: self
+                .layers()
+                .find_map(|(_, settings)| settings.id_charset)
+                .unwrap_or_default(),
+            max_length: self
+                .layers()
+                .find_map(|(_, settings)| settings.id_max_length)
+                .unwrap_or(DEFAULT_MAX_ID_LENGTH),
+            fold_case: self
+                .layers()
+                .find_map(|(_, settings)| settings.id_fold_case)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Effective value of `key` together with the layer it came from.
+    pub fn get(&self, key: &str) -> Result<(Option<String>, Source), AppError> {
+        for (source, settings) in self.layers() {
+            if let Some(value) = settings.get(key)? {
+                return Ok((Some(value), source));
+            }
+        }
+        let fallback = match key {
+            "format" => Some(enum_name(&self.format())),
+            "store" => self.store().ok().map(|path| path.display().to_string()),
+            "confirm" => Some(self.confirm().to_string()),
+            "color" => Some(enum_name(&self.color())),
+            "lock_timeout" => Some(format_duration(self.lock_timeout())),
+            "history_limit" => Some(self.history_limit().to_string()),
+            "id_charset" => Some(enum_name(&self.id_rules().charset)),
+            "id_max_length" => Some(self.id_rules().max_length.to_string()),
+            "id_fold_case" => Some(self.id_rules().fold_case.to_string()),
+            "backend" => Some(enum_name(&self.backend())),
+            _ => None,
+        };
+        Ok((fallback, Source::Default))
+    }
+
+    fn layers(&self) -> impl Iterator<Item = (Source, &Settings)> {
+        [(Source::Flag, &self.flags), (Source::Env, &self.env), (Source::File, &self.file)]
+            .into_iter()
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Flag => "flag",
+            Source::Env => "env",
+            Source::File => "file",
+            Source::Default => "default",
+        })
+    }
+}
+
+/// Location of the config file, in order of precedence:
+///
+/// 1. `$RS_CLI_TMPL_CONFIG`
+/// 2. `$XDG_CONFIG_HOME/rs-cli-tmpl/config.toml`
+/// 3. `$HOME/.config/rs-cli-tmpl/config.toml`
+pub fn config_path() -> Result<PathBuf, AppError> {
+    resolve_config_path(|name| env::var_os(name))
+}
+
+fn resolve_config_path(lookup: impl Fn(&str) -> Option<OsString>) -> Result<PathBuf, AppError> {
+    let var = |name: &str| lookup(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    if let Some(path) = var(CONFIG_ENV_VAR) {
+        return Ok(path);
+    }
+    if let Some(config_home) = var("XDG_CONFIG_HOME") {
+        return Ok(config_home.join("rs-cli-tmpl").join(CONFIG_FILE_NAME));
+    }
+    var("HOME")
+        .map(|home| home.join(".config").join("rs-cli-tmpl").join(CONFIG_FILE_NAME))
+        .ok_or_else(|| AppError::config_error("HOME environment variable not set"))
+}
+
+fn validate_key(key: &str) -> Result<&'static str, AppError> {
+    KEYS.into_iter().find(|candidate| *candidate == key).ok_or_else(|| {
+        AppError::config_error(format!(
+            "unknown config key '{key}' (expected one of: {})",
+            KEYS.join(", ")
+        ))
+    })
+}
+
+fn parse_enum<T: ValueEnum>(key: &str, value: &str) -> Result<T, AppError> {
+    T::from_str(value, true).map_err(|_| {
+        let expected: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|variant| variant.to_possible_value())
+            .map(|possible| possible.get_name().to_string())
+            .collect();
+        AppError::config_error(format!(
+            "invalid value '{value}' for {key} (expected one of: {})",
+            expected.join(", ")
+        ))
+    })
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, AppError> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(AppError::config_error(format!(
+            "invalid value '{value}' for {key} (expected true or false)"
+        ))),
+    }
+}
+
+fn parse_count(key: &str, value: &str) -> Result<usize, AppError> {
+    value.parse().map_err(|_| {
+        AppError::config_error(format!(
+            "invalid value '{value}' for {key} (expected a non-negative integer)"
+        ))
+    })
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<usize, AppError> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(AppError::config_error(format!(
+            "invalid value '{value}' for {key} (expected a positive integer)"
+        ))),
+    }
+}
+
+fn parse_duration(key: &str, value: &str) -> Result<Duration, AppError> {
+    humantime::parse_duration(value).map_err(|err| {
+        AppError::config_error(format!(
+            "invalid value '{value}' for {key} (expected e.g. 10s): {err}"
+        ))
+    })
+}
+
+fn format_duration(duration: Duration) -> String {
+    humantime::format_duration(duration).to_string()
+}
+
+/// Serde adapter storing durations as human-readable text such as `"1m 30s"`.
+mod duration_text {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => s.serialize_str(&super::format_duration(*duration)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        let text = String::deserialize(d)?;
+        humantime::parse_duration(&text).map(Some).map_err(de::Error::custom)
+    }
+}
+
+fn enum_name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().map(|possible| possible.get_name().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.into())
+    }
+
+    #[test]
+    fn settings_round_trip_through_toml_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+        let mut settings = Settings::default();
+        settings.set("format", "json").unwrap();
+        settings.set("confirm", "yes").unwrap();
+        settings.set("editor", "vim -n").unwrap();
+        settings.set("lock_timeout", "1m 30s").unwrap();
+
+        settings.save(&path).expect("save should succeed");
+
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("format = \"json\""), "unexpected file contents: {text}");
+        assert!(text.contains("lock_timeout = \"1m 30s\""), "unexpected file contents: {text}");
+    }
+
+    #[test]
+    fn load_treats_missing_file_as_empty_and_rejects_unknown_keys() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Settings::load(&dir.path().join("absent.toml")).unwrap(), Settings::default());
+
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "colour = \"never\"\n").unwrap();
+        assert!(matches!(Settings::load(&path), Err(AppError::ConfigError(_))));
+    }
+
+    #[test]
+    fn set_rejects_unknown_keys_and_invalid_values() {
+        let mut settings = Settings::default();
+
+        let err = settings.set("colour", "never").unwrap_err();
+        assert!(err.to_string().contains("unknown config key 'colour'"));
+
+        let err = settings.set("format", "xml").unwrap_err();
+        assert!(err.to_string().contains("expected one of: human, json, ndjson, tsv"));
+
+        assert!(settings.set("confirm", "maybe").is_err());
+        assert!(settings.set("lock_timeout", "soon").is_err());
+        assert!(settings.set("history_limit", "-1").is_err());
+        assert!(settings.set("id_charset", "latin1").is_err());
+        assert!(settings.set("id_max_length", "0").is_err());
+        assert!(settings.set("id_fold_case", "sometimes").is_err());
+        assert!(settings.set("backend", "postgres").is_err());
+    }
+
+    #[test]
+    fn env_layer_reads_prefixed_and_standard_variables() {
+        let vars = [
+            ("RS_CLI_TMPL_FORMAT", "tsv"),
+            ("RS_CLI_TMPL_HOME", "/env/store"),
+            ("EDITOR", "nano"),
+            ("RS_CLI_TMPL_CONFIRM", "1"),
+            ("NO_COLOR", "1"),
+            ("RS_CLI_TMPL_LOCK_TIMEOUT", "250ms"),
+            ("RS_CLI_TMPL_HISTORY_LIMIT", "3"),
+            ("RS_CLI_TMPL_ID_CHARSET", "unicode"),
+            ("RS_CLI_TMPL_ID_MAX_LENGTH", "64"),
+            ("RS_CLI_TMPL_ID_FOLD_CASE", "true"),
+            ("RS_CLI_TMPL_BACKEND", "sqlite"),
+        ];
+
+        let settings = Settings::from_lookup(lookup(&vars)).unwrap();
+
+        assert_eq!(settings.format, Some(OutputFormat::Tsv));
+        assert_eq!(settings.store, Some(PathBuf::from("/env/store")));
+        assert_eq!(settings.editor.as_deref(), Some("nano"));
+        assert_eq!(settings.confirm, Some(true));
+        assert_eq!(settings.color, Some(ColorMode::Never));
+        assert_eq!(settings.lock_timeout, Some(Duration::from_millis(250)));
+        assert_eq!(settings.history_limit, Some(3));
+        assert_eq!(settings.id_charset, Some(IdCharset::Unicode));
+        assert_eq!(settings.id_max_length, Some(64));
+        assert_eq!(settings.id_fold_case, Some(true));
+        assert_eq!(settings.backend, Some(Backend::Sqlite));
+    }
+
+    #[test]
+    fn layers_merge_in_precedence_order() {
+        let flags = Settings { format: Some(OutputFormat::Json), ..Settings::default() };
+        let env = Settings {
+            format: Some(OutputFormat::Tsv),
+            editor: Some("env-editor".to_string()),
+            ..Settings::default()
+        };
+        let file = Settings {
+            format: Some(OutputFormat::Ndjson),
+            editor: Some("file-editor".to_string()),
+            confirm: Some(true),
+            ..Settings::default()
+        };
+
+        let config = Config::from_layers(flags, env, file);
+
+        assert_eq!(config.format(), OutputFormat::Json);
+        assert_eq!(config.editor().as_deref(), Some("env-editor"));
+        assert!(config.confirm());
+        assert_eq!(config.color(), ColorMode::Auto);
+        assert_eq!(config.lock_timeout(), DEFAULT_LOCK_TIMEOUT);
+        assert_eq!(config.history_limit(), DEFAULT_HISTORY_LIMIT);
+        assert_eq!(config.id_rules(), IdRules::default());
+        assert_eq!(config.backend(), Backend::Filesystem);
+        assert_eq!(config.get("format").unwrap(), (Some("json".to_string()), Source::Flag));
+        assert_eq!(config.get("confirm").unwrap(), (Some("true".to_string()), Source::File));
+        assert_eq!(config.get("color").unwrap(), (Some("auto".to_string()), Source::Default));
+    }
+
+    #[test]
+    fn config_path_prefers_explicit_override() {
+        let vars = [(CONFIG_ENV_VAR, "/etc/tmpl.toml"), ("HOME", "/home/user")];
+        assert_eq!(resolve_config_path(lookup(&vars)).unwrap(), PathBuf::from("/etc/tmpl.toml"));
+
+        let vars = [("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/home/user")];
+        assert_eq!(
+            resolve_config_path(lookup(&vars)).unwrap(),
+            PathBuf::from("/xdg/rs-cli-tmpl/config.toml")
+        );
+
+        let vars = [("HOME", "/home/user")];
+        assert_eq!(
+            resolve_config_path(lookup(&vars)).unwrap(),
+            PathBuf::from("/home/user/.config/rs-cli-tmpl/config.toml")
+        );
+    }
+}
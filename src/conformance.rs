@@ -0,0 +1,320 @@
+//! Behavior every [`Storage`] backend must share.
+//!
+//! Each check receives an empty storage and panics on the first disagreement, so a backend
+//! conforms when all of them pass. The module is compiled with the `testing` cargo feature,
+//! letting backends outside this crate run the same suite as [`crate::FilesystemStorage`] and
+//! [`crate::MemoryStorage`], usually through [`storage_conformance_tests!`](crate::storage_conformance_tests)
+//! which expands to one test per check.
+
+use crate::error::AppError;
+use crate::id::{DEFAULT_MAX_ID_LENGTH, IdCharset, IdRules, ItemId};
+use crate::storage::Storage;
+use std::io::Cursor;
+use std::thread;
+
+fn id(raw: &str) -> ItemId {
+    ItemId::parse(raw).expect("conformance identifiers are valid")
+}
+
+fn ids(storage: &impl Storage) -> Vec<String> {
+    storage.list_items().unwrap().into_iter().map(String::from).collect()
+}
+
+/// Added content reads back byte for byte, with metadata describing it.
+pub fn add_then_read_back(storage: &impl Storage) {
+    let binary = [0u8, 159, 255, b'\n'];
+    storage.add_item(&id("note"), b"hello", Some("text/plain")).unwrap();
+    storage.add_item_from_reader(&id("blob"), &mut Cursor::new(binary), None).unwrap();
+
+    assert_eq!(storage.get_item(&id("note")).unwrap(), b"hello");
+    let mut streamed = Vec::new();
+    assert_eq!(storage.read_item_into(&id("blob"), &mut streamed).unwrap(), 4);
+    assert_eq!(streamed, binary);
+
+    let metadata = storage.item_metadata(&id("note")).unwrap();
+    assert_eq!(metadata.size, 5);
+    assert_eq!(metadata.version, 1);
+    assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+    assert_eq!(metadata.sha256, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+}
+
+/// Adding over an existing item fails and leaves it untouched.
+pub fn add_refuses_existing_ids(storage: &impl Storage) {
+    storage.add_item(&id("note"), b"first", None).unwrap();
+
+    let result = storage.add_item(&id("note"), b"second", None);
+    assert!(matches!(result, Err(AppError::ItemAlreadyExists(ref taken)) if taken == "note"));
+    assert_eq!(storage.get_item(&id("note")).unwrap(), b"first");
+}
+
+/// Every operation on an absent item fails with `ItemNotFound` naming it.
+pub fn missing_items_are_not_found(storage: &impl Storage) {
+    let missing = id("missing");
+    let not_found = |result: Result<(), AppError>| {
+        assert!(
+            matches!(result, Err(AppError::ItemNotFound(ref name)) if name == "missing"),
+            "expected ItemNotFound, got {result:?}"
+        );
+    };
+
+    not_found(storage.get_item(&missing).map(drop));
+    not_found(storage.read_item_into(&missing, &mut Vec::new()).map(drop));
+    not_found(storage.item_metadata(&missing).map(drop));
+    not_found(storage.update_item(&missing, b"x", None));
+    not_found(storage.append_item(&missing, b"x"));
+    not_found(storage.add_tag(&missing, "tag").map(drop));
+    not_found(storage.remove_tag(&missing, "tag").map(drop));
+    not_found(storage.list_versions(&missing).map(drop));
+    not_found(storage.read_version_into(&missing, 1, &mut Vec::new()).map(drop));
+    not_found(storage.delete_item(&missing));
+    not_found(storage.purge_item(&missing));
+    not_found(storage.restore_item(&missing));
+    assert!(storage.list_items().unwrap().is_empty());
+}
+
+/// Updates and appends bump the version and keep prior revisions readable.
+pub fn changes_keep_prior_revisions(storage: &impl Storage) {
+    let note = id("note");
+    storage.add_item(&note, b"one", Some("text/plain")).unwrap();
+    storage.update_item(&note, b"two", None).unwrap();
+    storage.append_item(&note, b"+three").unwrap();
+
+    assert_eq!(storage.get_item(&note).unwrap(), b"two+three");
+    let versions = storage.list_versions(&note).unwrap();
+    assert_eq!(versions.iter().map(|metadata| metadata.version).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(versions[2].size, 9);
+    assert_eq!(versions[2].content_type.as_deref(), Some("text/plain"));
+
+    let mut first = Vec::new();
+    storage.read_version_into(&note, 1, &mut first).unwrap();
+    assert_eq!(first, b"one");
+    let result = storage.read_version_into(&note, 4, &mut Vec::new());
+    assert!(matches!(result, Err(AppError::VersionNotFound(_, 4))));
+
+    storage.update_item(&note, b"four", Some("text/markdown")).unwrap();
+    let metadata = storage.item_metadata(&note).unwrap();
+    assert_eq!((metadata.version, metadata.content_type.as_deref()), (4, Some("text/markdown")));
+}
+
+/// Tags are validated, reported as changed only when they change, and survive updates.
+pub fn tags_attach_and_detach(storage: &impl Storage) {
+    let note = id("note");
+    storage.add_item(&note, b"x", None).unwrap();
+
+    assert!(storage.add_tag(&note, "work").unwrap());
+    assert!(!storage.add_tag(&note, "work").unwrap());
+    assert!(matches!(storage.add_tag(&note, "no spaces"), Err(AppError::ConfigError(_))));
+    storage.update_item(&note, b"y", None).unwrap();
+    assert!(storage.item_metadata(&note).unwrap().tags.contains("work"));
+
+    assert!(storage.remove_tag(&note, "work").unwrap());
+    assert!(!storage.remove_tag(&note, "work").unwrap());
+    assert!(storage.item_metadata(&note).unwrap().tags.is_empty());
+}
+
+/// Tags given with a write are stored with the content, replacing the item's tags on an update,
+/// and invalid ones stop the write.
+pub fn tagged_writes_store_tags_with_content(storage: &impl Storage) {
+    let note = id("note");
+    let tags = ["work".to_string(), "urgent".to_string()];
+    let invalid = ["fine".to_string(), "no spaces".to_string()];
+
+    let result = storage.add_tagged_item_from_reader(&note, &mut Cursor::new("x"), None, &invalid);
+    assert!(matches!(result, Err(AppError::ConfigError(_))));
+    assert!(storage.list_items().unwrap().is_empty());
+
+    storage.add_tagged_item_from_reader(&note, &mut Cursor::new("one"), None, &tags).unwrap();
+    let metadata = storage.item_metadata(&note).unwrap();
+    assert_eq!((metadata.version, metadata.tags.len()), (1, 2));
+
+    let later = ["later".to_string()];
+    storage.update_tagged_item_from_reader(&note, &mut Cursor::new("two"), None, &later).unwrap();
+    let result =
+        storage.update_tagged_item_from_reader(&note, &mut Cursor::new(""), None, &invalid);
+    assert!(matches!(result, Err(AppError::ConfigError(_))));
+    assert_eq!(storage.get_item(&note).unwrap(), b"two");
+    let metadata = storage.item_metadata(&note).unwrap();
+    assert_eq!(metadata.version, 2);
+    assert_eq!(metadata.tags.iter().collect::<Vec<_>>(), ["later"]);
+    storage.update_item(&note, b"three", None).unwrap();
+    assert_eq!(storage.item_metadata(&note).unwrap().tags.iter().collect::<Vec<_>>(), ["later"]);
+
+    let result =
+        storage.update_tagged_item_from_reader(&id("missing"), &mut Cursor::new(""), None, &tags);
+    assert!(matches!(result, Err(AppError::ItemNotFound(_))));
+}
+
+/// Listings hold every live item in every namespace, sorted by id.
+pub fn list_items_is_sorted_by_id(storage: &impl Storage) {
+    for name in ["zeta", "team/b", "alpha", "team/a/deep", "Team2"] {
+        storage.add_item(&id(name), name.as_bytes(), None).unwrap();
+    }
+    storage.add_item(&id("gone"), b"x", None).unwrap();
+    storage.delete_item(&id("gone")).unwrap();
+
+    assert_eq!(ids(storage), ["Team2", "alpha", "team/a/deep", "team/b", "zeta"]);
+}
+
+/// A name is either an item or a namespace, never both.
+pub fn items_and_namespaces_do_not_overlap(storage: &impl Storage) {
+    storage.add_item(&id("team/token"), b"x", None).unwrap();
+    storage.add_item(&id("solo"), b"x", None).unwrap();
+
+    assert!(matches!(storage.add_item(&id("team"), b"x", None), Err(AppError::ConfigError(_))));
+    let result = storage.add_item(&id("solo/child"), b"x", None);
+    assert!(
+        matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("'solo'"))
+    );
+    assert!(matches!(storage.delete_item(&id("team")), Err(AppError::ItemNotFound(_))));
+
+    storage.delete_item(&id("team/token")).unwrap();
+    storage.add_item(&id("team"), b"now an item", None).unwrap();
+    assert_eq!(ids(storage), ["solo", "team"]);
+}
+
+/// New names differing only in letter case from existing items or namespaces are refused.
+pub fn case_collisions_are_refused(storage: &impl Storage) {
+    storage.add_item(&id("Foo"), b"x", None).unwrap();
+    storage.add_item(&id("team/readme"), b"x", None).unwrap();
+
+    let result = storage.add_item(&id("foo"), b"x", None);
+    assert!(matches!(result, Err(AppError::CaseCollision(_, ref existing)) if existing == "Foo"));
+    let result = storage.add_item(&id("TEAM/notes"), b"x", None);
+    assert!(matches!(result, Err(AppError::CaseCollision(_, ref existing)) if existing == "team"));
+    storage.add_item(&id("team/README.md"), b"x", None).unwrap();
+}
+
+/// Deleted items wait in the trash until restored or purged.
+pub fn trash_round_trip(storage: &impl Storage) {
+    let note = id("team/note");
+    storage.add_item(&note, b"first", None).unwrap();
+    storage.delete_item(&note).unwrap();
+    storage.add_item(&note, b"second", None).unwrap();
+    storage.delete_item(&note).unwrap();
+
+    let trash = storage.list_trash().unwrap();
+    assert_eq!(
+        trash.iter().map(|entry| (entry.id.as_str(), entry.size)).collect::<Vec<_>>(),
+        [("team/note", 5), ("team/note", 6)]
+    );
+    assert!(ids(storage).is_empty());
+
+    storage.restore_item(&note).unwrap();
+    assert_eq!(storage.get_item(&note).unwrap(), b"second");
+    assert!(matches!(storage.restore_item(&note), Err(AppError::ItemAlreadyExists(_))));
+
+    storage.purge_item(&note).unwrap();
+    assert!(matches!(storage.get_item(&note), Err(AppError::ItemNotFound(_))));
+    assert_eq!(storage.list_trash().unwrap().len(), 1);
+
+    let removed = storage.empty_trash(None).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert!(storage.list_trash().unwrap().is_empty());
+    assert!(matches!(storage.restore_item(&note), Err(AppError::ItemNotFound(_))));
+}
+
+/// Search candidates, when offered, never omit an item containing the query.
+pub fn search_candidates_cover_matches(storage: &impl Storage) {
+    storage.add_item(&id("match"), b"buy more milk", None).unwrap();
+    storage.add_item(&id("other"), b"nothing here", None).unwrap();
+
+    if let Some(candidates) = storage.search_candidates("Milk").unwrap() {
+        assert!(candidates.contains(&id("match")), "candidates {candidates:?} omit a match");
+    }
+}
+
+/// Every identifier the default or Unicode rules accept is stored and listed back as given.
+pub fn valid_ids_round_trip(storage: &impl Storage) {
+    let unicode = IdRules { charset: IdCharset::Unicode, ..IdRules::default() };
+    let mut names = vec![
+        id("a.b_c-D"),
+        id("deeply/nested/name/space/item"),
+        id(&"x".repeat(DEFAULT_MAX_ID_LENGTH)),
+        unicode.parse("cafe\u{301}/\u{65e5}\u{672c}").unwrap(),
+    ];
+    for name in &names {
+        storage.add_item(name, name.as_bytes(), None).unwrap();
+    }
+
+    names.sort();
+    assert_eq!(storage.list_items().unwrap(), names);
+    for name in &names {
+        assert_eq!(storage.get_item(name).unwrap(), name.as_bytes());
+    }
+}
+
+/// Writers sharing the storage across threads never lose a change, and exactly one of them
+/// creates an id they all race for.
+pub fn concurrent_writes_are_serialized<S: Storage + Sync>(storage: &S) {
+    const WRITERS: usize = 8;
+    storage.add_item(&id("log"), b"", None).unwrap();
+
+    let created = thread::scope(|scope| {
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|writer| {
+                scope.spawn(move || {
+                    storage.append_item(&id("log"), b"x").unwrap();
+                    storage.add_item(&id(&format!("writer-{writer}")), b"own", None).unwrap();
+                    match storage.add_item(&id("contended"), b"race", None) {
+                        Ok(()) => true,
+                        Err(AppError::ItemAlreadyExists(_)) => false,
+                        Err(err) => panic!("unexpected error: {err:?}"),
+                    }
+                })
+            })
+            .collect();
+        writers.into_iter().map(|writer| writer.join().unwrap()).filter(|created| *created).count()
+    });
+
+    assert_eq!(created, 1, "exactly one writer should create the contended item");
+    assert_eq!(storage.get_item(&id("log")).unwrap(), [b'x'; WRITERS]);
+    assert_eq!(storage.list_items().unwrap().len(), WRITERS + 2);
+}
+
+/// Expand to a `conformance` module holding one `#[test]` per check for a backend.
+///
+/// `$setup` is evaluated afresh for every test and yields `(guard, storage)`; the guard, such as
+/// a temporary directory, is kept alive until the check finishes:
+///
+/// ```rust,ignore
+/// rs_cli_tmpl::storage_conformance_tests!({
+///     let dir = tempfile::TempDir::new().unwrap();
+///     let storage = MyStorage::open(dir.path());
+///     (dir, storage)
+/// });
+/// ```
+#[macro_export]
+macro_rules! storage_conformance_tests {
+    ($setup:expr) => {
+        $crate::storage_conformance_tests!(
+            $setup;
+            add_then_read_back,
+            add_refuses_existing_ids,
+            missing_items_are_not_found,
+            changes_keep_prior_revisions,
+            tags_attach_and_detach,
+            tagged_writes_store_tags_with_content,
+            list_items_is_sorted_by_id,
+            items_and_namespaces_do_not_overlap,
+            case_collisions_are_refused,
+            trash_round_trip,
+            search_candidates_cover_matches,
+            valid_ids_round_trip,
+            concurrent_writes_are_serialized,
+        );
+    };
+    ($setup:expr; $($check:ident),+ $(,)?) => {
+        mod conformance {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                fn $check() {
+                    let (_guard, storage) = $setup;
+                    $crate::conformance::$check(&storage);
+                }
+            )+
+        }
+    };
+}
@@ -1,33 +1,145 @@
 use crate::core::Execute;
 use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::validate_tags;
 use crate::storage::Storage;
 
 /// Minimal example command illustrating how to write to the storage layer.
 pub struct AddItem<'a> {
-    pub id: &'a str,
-    pub content: &'a str,
+    pub id: &'a ItemId,
+    pub content: &'a [u8],
+    pub content_type: Option<&'a str>,
+    /// Tags attached to the item in the same write as its content, replacing those of an item
+    /// overwritten with `force`.
+    pub tags: &'a [String],
+    /// Overwrite the item if it already exists instead of failing.
+    pub force: bool,
 }
 
 impl Execute<()> for AddItem<'_> {
     fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
-        storage.add_item(self.id, self.content)
+        validate_tags(self.tags.iter().map(String::as_str))?;
+        let (id, content_type, tags) = (self.id, self.content_type, self.tags);
+        let add =
+            || storage.add_tagged_item_from_reader(id, &mut &*self.content, content_type, tags);
+        if !self.force {
+            return add();
+        }
+        match storage.update_tagged_item_from_reader(id, &mut &*self.content, content_type, tags) {
+            Err(AppError::ItemNotFound(_)) => add(),
+            result => result,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::test_support::MockStorage;
+    use crate::core::test_support::{MockStorage, id};
 
     #[test]
     fn add_item_forwards_to_storage() {
         let storage = MockStorage::default();
-        let command = AddItem { id: "demo", content: "example" };
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &[],
+            force: false,
+        };
 
         command.execute(&storage).expect("execution should succeed");
 
         let calls = storage.add_calls.borrow();
         assert_eq!(calls.len(), 1);
-        assert_eq!(calls[0], ("demo".to_string(), "example".to_string()));
+        assert_eq!(calls[0], ("demo".to_string(), b"example".to_vec()));
+    }
+
+    #[test]
+    fn add_item_attaches_tags() {
+        let storage = MockStorage::default();
+        let tags = vec!["work".to_string()];
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &tags,
+            force: false,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert!(storage.tags_value.borrow()["demo"].contains("work"));
+    }
+
+    #[test]
+    fn add_item_rejects_invalid_tags_before_writing() {
+        let storage = MockStorage::default();
+        let tags = vec!["not valid".to_string()];
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &tags,
+            force: false,
+        };
+
+        assert!(matches!(command.execute(&storage), Err(AppError::ConfigError(_))));
+        assert!(storage.add_calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn add_item_fails_for_existing_item_without_force() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["demo"]);
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &[],
+            force: false,
+        };
+
+        let result = command.execute(&storage);
+
+        assert!(matches!(result, Err(AppError::ItemAlreadyExists(ref id)) if id == "demo"));
+        assert!(storage.update_calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn add_item_with_force_overwrites_existing_item_and_its_tags() {
+        let storage = MockStorage::default();
+        storage.set_tags("demo", &["old", "work"]);
+        let tags = vec!["work".to_string(), "new".to_string()];
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &tags,
+            force: true,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(storage.update_calls.borrow().len(), 1);
+        assert!(storage.add_calls.borrow().is_empty());
+        assert_eq!(storage.tags_value.borrow()["demo"].iter().collect::<Vec<_>>(), ["new", "work"]);
+    }
+
+    #[test]
+    fn add_item_with_force_creates_missing_item() {
+        let storage = MockStorage::default();
+        let command = AddItem {
+            id: &id("demo"),
+            content: b"example",
+            content_type: None,
+            tags: &[],
+            force: true,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(storage.update_calls.borrow().len(), 1);
+        assert_eq!(storage.add_calls.borrow().len(), 1);
     }
 }
@@ -0,0 +1,43 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Example command for adding content to the end of an existing item.
+pub struct AppendItem<'a> {
+    pub id: &'a ItemId,
+    pub content: &'a [u8],
+}
+
+impl Execute<()> for AppendItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
+        storage.append_item(self.id, self.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn append_item_forwards_to_storage() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["log"]);
+        let command = AppendItem { id: &id("log"), content: b"more" };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        let calls = storage.append_calls.borrow();
+        assert_eq!(calls.as_slice(), [("log".to_string(), b"more".to_vec())]);
+    }
+
+    #[test]
+    fn append_item_fails_for_missing_item() {
+        let storage = MockStorage::default();
+        let command = AppendItem { id: &id("missing"), content: b"more" };
+
+        let result = command.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
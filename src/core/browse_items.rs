@@ -0,0 +1,131 @@
+use crate::core::Execute;
+use crate::core::filter_items::FilterItems;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+use std::collections::BTreeSet;
+
+/// One line of a namespace listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowseEntry {
+    Item(ItemId),
+    /// A namespace holding deeper items, written with a trailing `/` such as `team/`.
+    Namespace(String),
+}
+
+impl BrowseEntry {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BrowseEntry::Item(id) => id.as_str(),
+            BrowseEntry::Namespace(namespace) => namespace,
+        }
+    }
+}
+
+/// Lists the entries directly inside a namespace, like `ls`, or every item below it.
+///
+/// Without `recursive`, deeper items are folded into their namespace, listed once. An item
+/// whose id is `prefix` itself is listed too. Tag filters apply to the items before they are
+/// folded.
+pub struct BrowseItems<'a> {
+    /// Namespace to browse, such as `team/service`; `None` browses the whole store.
+    pub prefix: Option<&'a str>,
+    pub recursive: bool,
+    pub tags: &'a [String],
+    pub match_any: bool,
+}
+
+impl Execute<Vec<BrowseEntry>> for BrowseItems<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<BrowseEntry>, AppError> {
+        let items = FilterItems { tags: self.tags, match_any: self.match_any }.execute(storage)?;
+        let prefix = self.prefix.map(|prefix| prefix.trim_end_matches('/')).unwrap_or_default();
+        let base = if prefix.is_empty() { String::new() } else { format!("{prefix}/") };
+
+        let mut entries = Vec::new();
+        let mut namespaces = BTreeSet::new();
+        for id in items {
+            let Some(rest) = id.strip_prefix(base.as_str()) else {
+                if *id == *prefix {
+                    entries.push(BrowseEntry::Item(id));
+                }
+                continue;
+            };
+            match rest.split_once('/') {
+                Some((namespace, _)) if !self.recursive => {
+                    let namespace = format!("{base}{namespace}/");
+                    if namespaces.insert(namespace.clone()) {
+                        entries.push(BrowseEntry::Namespace(namespace));
+                    }
+                }
+                _ => entries.push(BrowseEntry::Item(id)),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    fn namespaced_storage() -> MockStorage {
+        let storage = MockStorage::default();
+        storage.set_list_items([
+            "solo",
+            "team/readme",
+            "team/service/key",
+            "team/service/token",
+            "teamwork",
+        ]);
+        storage
+    }
+
+    fn browse(storage: &MockStorage, prefix: Option<&str>, recursive: bool) -> Vec<String> {
+        let command = BrowseItems { prefix, recursive, tags: &[], match_any: false };
+        command.execute(storage).unwrap().iter().map(|entry| entry.as_str().to_string()).collect()
+    }
+
+    #[test]
+    fn browse_items_folds_namespaces_at_the_top_level() {
+        let storage = namespaced_storage();
+
+        let entries = browse(&storage, None, false);
+        assert_eq!(entries, vec!["solo", "team/", "teamwork"]);
+    }
+
+    #[test]
+    fn browse_items_lists_inside_a_prefix() {
+        let storage = namespaced_storage();
+
+        let entries = browse(&storage, Some("team"), false);
+        assert_eq!(entries, vec!["team/readme", "team/service/"]);
+
+        let entries = browse(&storage, Some("team/service/"), false);
+        assert_eq!(entries, vec!["team/service/key", "team/service/token"]);
+
+        let entries = browse(&storage, Some("solo"), false);
+        assert_eq!(entries, vec!["solo"]);
+    }
+
+    #[test]
+    fn browse_items_recursive_lists_every_item_below_the_prefix() {
+        let storage = namespaced_storage();
+
+        let entries = browse(&storage, Some("team"), true);
+        assert_eq!(entries, vec!["team/readme", "team/service/key", "team/service/token"]);
+        assert_eq!(browse(&storage, None, true).len(), 5);
+    }
+
+    #[test]
+    fn browse_items_applies_tags_before_folding() {
+        let storage = MockStorage::default();
+        storage.set_tags("team/service/token", &["secret"]);
+        storage.set_tags("team/readme", &[]);
+        let tags = vec!["secret".to_string()];
+
+        let command = BrowseItems { prefix: None, recursive: false, tags: &tags, match_any: false };
+        let entries = command.execute(&storage).unwrap();
+        assert_eq!(entries, vec![BrowseEntry::Namespace("team/".to_string())]);
+    }
+}
@@ -1,31 +1,47 @@
 use crate::core::Execute;
 use crate::error::AppError;
+use crate::id::ItemId;
 use crate::storage::Storage;
 
 /// Example command for removing an item from storage.
+///
+/// Items are moved to the trash unless `purge` asks for permanent removal.
 pub struct DeleteItem<'a> {
-    pub id: &'a str,
+    pub id: &'a ItemId,
+    pub purge: bool,
 }
 
 impl Execute<()> for DeleteItem<'_> {
     fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
-        storage.delete_item(self.id)
+        if self.purge { storage.purge_item(self.id) } else { storage.delete_item(self.id) }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::test_support::MockStorage;
+    use crate::core::test_support::{MockStorage, id};
 
     #[test]
     fn delete_item_forwards_to_storage() {
         let storage = MockStorage::default();
-        let command = DeleteItem { id: "demo" };
+        let command = DeleteItem { id: &id("demo"), purge: false };
 
         command.execute(&storage).expect("execution should succeed");
 
         let calls = storage.delete_calls.borrow();
         assert_eq!(calls.as_slice(), ["demo".to_string()]);
+        assert!(storage.purge_calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn delete_item_with_purge_skips_trash() {
+        let storage = MockStorage::default();
+        let command = DeleteItem { id: &id("demo"), purge: true };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(storage.purge_calls.borrow().as_slice(), ["demo".to_string()]);
+        assert!(storage.delete_calls.borrow().is_empty());
     }
 }
@@ -0,0 +1,32 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::ItemMetadata;
+use crate::storage::Storage;
+
+/// Example command for reading an item's metadata sidecar.
+pub struct DescribeItem<'a> {
+    pub id: &'a ItemId,
+}
+
+impl Execute<ItemMetadata> for DescribeItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<ItemMetadata, AppError> {
+        storage.item_metadata(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn describe_item_returns_storage_metadata() {
+        let storage = MockStorage::default();
+        let metadata = ItemMetadata::new(b"example", Some("text/plain"));
+        storage.set_metadata(metadata.clone());
+
+        let result = DescribeItem { id: &id("demo") }.execute(&storage).expect("should succeed");
+        assert_eq!(result, metadata);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::metadata::TrashEntry;
+use crate::storage::Storage;
+use std::time::Duration;
+
+/// Permanently removes trashed items, optionally only those deleted at least `older_than` ago.
+pub struct EmptyTrash {
+    pub older_than: Option<Duration>,
+}
+
+impl Execute<Vec<TrashEntry>> for EmptyTrash {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<TrashEntry>, AppError> {
+        storage.empty_trash(self.older_than)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    #[test]
+    fn empty_trash_forwards_age_cutoff() {
+        let storage = MockStorage::default();
+        storage.set_trash(["gone"]);
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+
+        let removed = EmptyTrash { older_than: Some(week) }.execute(&storage).unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(storage.empty_trash_calls.borrow().as_slice(), [Some(week)]);
+    }
+}
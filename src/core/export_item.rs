@@ -0,0 +1,69 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+use std::cell::RefCell;
+use std::io::Write;
+
+/// Streams an item's content into a writer, returning the number of bytes copied.
+pub struct ExportItem<'a> {
+    pub id: &'a ItemId,
+    /// Retained revision to export instead of the current content.
+    pub version: Option<u64>,
+    pub writer: RefCell<&'a mut dyn Write>,
+}
+
+impl Execute<u64> for ExportItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<u64, AppError> {
+        let mut writer = self.writer.borrow_mut();
+        match self.version {
+            Some(version) => storage.read_version_into(self.id, version, *writer),
+            None => storage.read_item_into(self.id, *writer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn export_item_writes_storage_content() {
+        let storage = MockStorage::default();
+        storage.set_get_item(b"example");
+        let mut sink = Vec::new();
+        let command =
+            ExportItem { id: &id("demo"), version: None, writer: RefCell::new(&mut sink) };
+
+        let written = command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(written, 7);
+        assert_eq!(sink, b"example");
+    }
+
+    #[test]
+    fn export_item_reads_requested_version() {
+        let storage = MockStorage::default();
+        storage.set_versions(&["first", "second"]);
+        let mut sink = Vec::new();
+        let command =
+            ExportItem { id: &id("demo"), version: Some(1), writer: RefCell::new(&mut sink) };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(sink, b"first");
+    }
+
+    #[test]
+    fn export_item_propagates_not_found() {
+        let storage = MockStorage::default();
+        let mut sink = Vec::new();
+        let command =
+            ExportItem { id: &id("missing"), version: None, writer: RefCell::new(&mut sink) };
+
+        let result = command.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+        assert!(sink.is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::core::Execute;
+use crate::core::list_items::ListItems;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Lists the items carrying the given tags: all of them, or any of them with `match_any`.
+///
+/// Without tags every item is listed.
+pub struct FilterItems<'a> {
+    pub tags: &'a [String],
+    pub match_any: bool,
+}
+
+impl Execute<Vec<ItemId>> for FilterItems<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<ItemId>, AppError> {
+        let items = ListItems.execute(storage)?;
+        if self.tags.is_empty() {
+            return Ok(items);
+        }
+
+        let mut matching = Vec::new();
+        for id in items {
+            let tags = match storage.item_metadata(&id) {
+                Ok(metadata) => metadata.tags,
+                // Deleted by another process since it was listed.
+                Err(AppError::ItemNotFound(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            let matches = if self.match_any {
+                self.tags.iter().any(|tag| tags.contains(tag))
+            } else {
+                self.tags.iter().all(|tag| tags.contains(tag))
+            };
+            if matches {
+                matching.push(id);
+            }
+        }
+        Ok(matching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    fn tagged_storage() -> MockStorage {
+        let storage = MockStorage::default();
+        storage.set_tags("both", &["work", "urgent"]);
+        storage.set_tags("work-only", &["work"]);
+        storage.set_tags("untagged", &[]);
+        storage
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn filter_items_requires_every_tag_by_default() {
+        let storage = tagged_storage();
+        let tags = tags(&["work", "urgent"]);
+
+        let items = FilterItems { tags: &tags, match_any: false }.execute(&storage).unwrap();
+        assert_eq!(items, vec!["both"]);
+    }
+
+    #[test]
+    fn filter_items_with_match_any_accepts_any_tag() {
+        let storage = tagged_storage();
+        let tags = tags(&["urgent", "work"]);
+
+        let items = FilterItems { tags: &tags, match_any: true }.execute(&storage).unwrap();
+        assert_eq!(items, vec!["both", "work-only"]);
+    }
+
+    #[test]
+    fn filter_items_without_tags_lists_everything() {
+        let storage = tagged_storage();
+
+        let items = FilterItems { tags: &[], match_any: false }.execute(&storage).unwrap();
+        assert_eq!(items, vec!["both", "work-only", "untagged"]);
+    }
+}
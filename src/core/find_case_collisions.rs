@@ -0,0 +1,58 @@
+use crate::core::Execute;
+use crate::core::list_items::ListItems;
+use crate::error::AppError;
+use crate::storage::Storage;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Finds stored names that differ only in letter case, which share one directory on
+/// case-insensitive filesystems.
+///
+/// Items and namespaces are both compared, and each group lists the colliding spellings in
+/// order. Items below a colliding namespace are not reported again.
+pub struct FindCaseCollisions;
+
+impl Execute<Vec<Vec<String>>> for FindCaseCollisions {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<Vec<String>>, AppError> {
+        let mut spellings: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        let items = ListItems.execute(storage)?;
+        for id in &items {
+            for name in id.namespaces().chain([id.as_str()]) {
+                spellings.entry(name.to_lowercase()).or_default().insert(name);
+            }
+        }
+
+        let mut collisions = Vec::new();
+        let mut reported: Vec<String> = Vec::new();
+        for (folded, names) in spellings {
+            if names.len() < 2 || reported.iter().any(|outer| folded.starts_with(outer.as_str())) {
+                continue;
+            }
+            collisions.push(names.into_iter().map(str::to_string).collect());
+            reported.push(format!("{folded}/"));
+        }
+        Ok(collisions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    #[test]
+    fn find_case_collisions_groups_spellings_of_one_name() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["Foo", "Team/a", "Team/b", "foo", "other", "team/a", "team/c"]);
+
+        let collisions = FindCaseCollisions.execute(&storage).unwrap();
+        assert_eq!(collisions, vec![vec!["Foo", "foo"], vec!["Team", "team"]]);
+    }
+
+    #[test]
+    fn find_case_collisions_is_empty_for_distinct_names() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["alpha", "team/Notes", "team/readme"]);
+
+        assert!(FindCaseCollisions.execute(&storage).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Example command for reading an item's content back from storage.
+pub struct GetItem<'a> {
+    pub id: &'a ItemId,
+}
+
+impl Execute<Vec<u8>> for GetItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<u8>, AppError> {
+        storage.get_item(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn get_item_returns_storage_content() {
+        let storage = MockStorage::default();
+        storage.set_get_item(b"example");
+        let command = GetItem { id: &id("demo") };
+
+        let content = command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(content, b"example");
+        assert_eq!(storage.get_calls.borrow().as_slice(), ["demo".to_string()]);
+    }
+
+    #[test]
+    fn get_item_propagates_not_found() {
+        let storage = MockStorage::default();
+        let command = GetItem { id: &id("missing") };
+
+        let result = command.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
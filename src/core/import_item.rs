@@ -0,0 +1,109 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::validate_tags;
+use crate::storage::Storage;
+use std::cell::RefCell;
+use std::io::Read;
+
+/// Streaming counterpart of [`AddItem`](crate::core::add_item::AddItem) that copies content
+/// from a reader instead of a buffered slice.
+pub struct ImportItem<'a> {
+    pub id: &'a ItemId,
+    pub reader: RefCell<&'a mut dyn Read>,
+    pub content_type: Option<&'a str>,
+    /// Tags attached to the item in the same write as its content, replacing those of an item
+    /// overwritten with `force`.
+    pub tags: &'a [String],
+    /// Overwrite the item if it already exists instead of failing.
+    pub force: bool,
+}
+
+impl Execute<()> for ImportItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
+        validate_tags(self.tags.iter().map(String::as_str))?;
+        let mut reader = self.reader.borrow_mut();
+        let (id, content_type, tags) = (self.id, self.content_type, self.tags);
+        if !self.force {
+            return storage.add_tagged_item_from_reader(id, *reader, content_type, tags);
+        }
+        // The stream can only be consumed once, so pick the target before reading it.
+        match storage.item_metadata(id) {
+            Ok(_) => storage.update_tagged_item_from_reader(id, *reader, content_type, tags),
+            Err(AppError::ItemNotFound(_)) => {
+                storage.add_tagged_item_from_reader(id, *reader, content_type, tags)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+    use crate::metadata::ItemMetadata;
+
+    #[test]
+    fn import_item_streams_reader_into_storage() {
+        let storage = MockStorage::default();
+        let mut reader: &[u8] = b"streamed";
+        let command = ImportItem {
+            id: &id("demo"),
+            reader: RefCell::new(&mut reader),
+            content_type: None,
+            tags: &[],
+            force: false,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(
+            storage.add_calls.borrow().as_slice(),
+            [("demo".to_string(), b"streamed".to_vec())]
+        );
+    }
+
+    #[test]
+    fn import_item_with_force_updates_existing_item_and_its_tags() {
+        let storage = MockStorage::default();
+        storage.set_tags("demo", &["old"]);
+        storage.set_metadata(ItemMetadata::new(b"old", None));
+        let mut reader: &[u8] = b"new";
+        let tags = vec!["new".to_string()];
+        let command = ImportItem {
+            id: &id("demo"),
+            reader: RefCell::new(&mut reader),
+            content_type: None,
+            tags: &tags,
+            force: true,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(
+            storage.update_calls.borrow().as_slice(),
+            [("demo".to_string(), b"new".to_vec())]
+        );
+        assert!(storage.add_calls.borrow().is_empty());
+        assert_eq!(storage.tags_value.borrow()["demo"].iter().collect::<Vec<_>>(), ["new"]);
+    }
+
+    #[test]
+    fn import_item_with_force_creates_missing_item() {
+        let storage = MockStorage::default();
+        let mut reader: &[u8] = b"new";
+        let command = ImportItem {
+            id: &id("demo"),
+            reader: RefCell::new(&mut reader),
+            content_type: None,
+            tags: &[],
+            force: true,
+        };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        assert!(storage.update_calls.borrow().is_empty());
+        assert_eq!(storage.add_calls.borrow().as_slice(), [("demo".to_string(), b"new".to_vec())]);
+    }
+}
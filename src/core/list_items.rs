@@ -1,12 +1,13 @@
 use crate::core::Execute;
 use crate::error::AppError;
+use crate::id::ItemId;
 use crate::storage::Storage;
 
 /// Example command demonstrating how to read data from the storage layer.
 pub struct ListItems;
 
-impl Execute<Vec<String>> for ListItems {
-    fn execute(&self, storage: &impl Storage) -> Result<Vec<String>, AppError> {
+impl Execute<Vec<ItemId>> for ListItems {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<ItemId>, AppError> {
         storage.list_items()
     }
 }
@@ -22,6 +23,6 @@ mod tests {
         storage.set_list_items(["first", "second"]);
 
         let items = ListItems.execute(&storage).expect("execution should succeed");
-        assert_eq!(items, vec!["first".to_string(), "second".to_string()]);
+        assert_eq!(items, vec!["first", "second"]);
     }
 }
@@ -0,0 +1,30 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::metadata::TrashEntry;
+use crate::storage::Storage;
+
+/// Lists deleted items still held in the trash, oldest deletion first.
+pub struct ListTrash;
+
+impl Execute<Vec<TrashEntry>> for ListTrash {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<TrashEntry>, AppError> {
+        storage.list_trash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    #[test]
+    fn list_trash_returns_storage_entries() {
+        let storage = MockStorage::default();
+        storage.set_trash(["gone", "also-gone"]);
+
+        let entries = ListTrash.execute(&storage).unwrap();
+
+        let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["gone", "also-gone"]);
+    }
+}
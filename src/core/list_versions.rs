@@ -0,0 +1,41 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::ItemMetadata;
+use crate::storage::Storage;
+
+/// Lists the retained revisions of an item, oldest first.
+pub struct ListVersions<'a> {
+    pub id: &'a ItemId,
+}
+
+impl Execute<Vec<ItemMetadata>> for ListVersions<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<ItemMetadata>, AppError> {
+        storage.list_versions(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn list_versions_returns_storage_history() {
+        let storage = MockStorage::default();
+        storage.set_versions(&["one", "two"]);
+
+        let versions = ListVersions { id: &id("demo") }.execute(&storage).unwrap();
+
+        let numbers: Vec<u64> = versions.iter().map(|metadata| metadata.version).collect();
+        assert_eq!(numbers, vec![1, 2]);
+    }
+
+    #[test]
+    fn list_versions_propagates_not_found() {
+        let storage = MockStorage::default();
+
+        let result = ListVersions { id: &id("missing") }.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
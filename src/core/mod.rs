@@ -1,6 +1,28 @@
+//! Command structs holding the business rules, independent of storage and output.
+//!
+//! Implement [`Execute`] to add commands of your own; the structs here can be combined the same
+//! way, as [`browse_items::BrowseItems`] builds on [`filter_items::FilterItems`].
+
 pub mod add_item;
+pub mod append_item;
+pub mod browse_items;
 pub mod delete_item;
+pub mod describe_item;
+pub mod empty_trash;
+pub mod export_item;
+pub mod filter_items;
+pub mod find_case_collisions;
+pub mod get_item;
+pub mod import_item;
 pub mod list_items;
+pub mod list_trash;
+pub mod list_versions;
+pub mod restore_item;
+pub mod rollback_item;
+pub mod search_items;
+pub mod tag_item;
+pub mod untag_item;
+pub mod update_item;
 
 use crate::error::AppError;
 use crate::storage::Storage;
@@ -8,6 +30,10 @@ use crate::storage::Storage;
 #[cfg(test)]
 pub(crate) mod test_support;
 
-pub(crate) trait Execute<R> {
+/// A unit of business logic run against any [`Storage`] backend.
+///
+/// Each command is a struct holding its inputs and returning `R`; it reports nothing, leaving
+/// output to the [`crate::commands`] layer.
+pub trait Execute<R> {
     fn execute(&self, storage: &impl Storage) -> Result<R, AppError>;
 }
@@ -0,0 +1,52 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Brings the most recently deleted copy of an item back from the trash.
+pub struct RestoreItem<'a> {
+    pub id: &'a ItemId,
+}
+
+impl Execute<()> for RestoreItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
+        storage.restore_item(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn restore_item_takes_entry_out_of_trash() {
+        let storage = MockStorage::default();
+        storage.set_trash(["demo", "other"]);
+
+        RestoreItem { id: &id("demo") }.execute(&storage).expect("execution should succeed");
+
+        assert_eq!(storage.restore_calls.borrow().as_slice(), ["demo".to_string()]);
+        let remaining = storage.trash_values.borrow();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "other");
+    }
+
+    #[test]
+    fn restore_item_refuses_to_overwrite_live_item() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["demo"]);
+        storage.set_trash(["demo"]);
+
+        let result = RestoreItem { id: &id("demo") }.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemAlreadyExists(ref id)) if id == "demo"));
+    }
+
+    #[test]
+    fn restore_item_propagates_not_found() {
+        let storage = MockStorage::default();
+
+        let result = RestoreItem { id: &id("missing") }.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Restores a retained revision as a new revision of the item, keeping the history intact.
+pub struct RollbackItem<'a> {
+    pub id: &'a ItemId,
+    pub version: u64,
+}
+
+impl Execute<()> for RollbackItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
+        let versions = storage.list_versions(self.id)?;
+        let target = versions
+            .iter()
+            .find(|metadata| metadata.version == self.version)
+            .ok_or_else(|| AppError::VersionNotFound(self.id.to_string(), self.version))?;
+
+        let mut content = Vec::new();
+        storage.read_version_into(self.id, self.version, &mut content)?;
+        storage.update_item(self.id, &content, target.content_type.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn rollback_item_writes_old_content_as_update() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["demo"]);
+        storage.set_versions(&["first", "second"]);
+
+        RollbackItem { id: &id("demo"), version: 1 }.execute(&storage).expect("rollback succeeds");
+
+        assert_eq!(
+            storage.update_calls.borrow().as_slice(),
+            [("demo".to_string(), b"first".to_vec())]
+        );
+    }
+
+    #[test]
+    fn rollback_item_fails_for_unknown_version() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["demo"]);
+        storage.set_versions(&["first"]);
+
+        let result = RollbackItem { id: &id("demo"), version: 7 }.execute(&storage);
+
+        assert!(matches!(result, Err(AppError::VersionNotFound(ref id, 7)) if id == "demo"));
+        assert!(storage.update_calls.borrow().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::core::Execute;
+use crate::core::list_items::ListItems;
+use crate::error::AppError;
+use crate::search::{self, LineMatcher, SearchMatch, SearchMode};
+use crate::storage::Storage;
+
+/// Finds the lines of every text item matching `query`, in item then line order.
+///
+/// Literal queries only read the items the storage's search index proposes, when it keeps one.
+/// Items are streamed line by line rather than loaded whole, and binary items are skipped.
+pub struct SearchItems<'a> {
+    pub query: &'a str,
+    pub mode: SearchMode,
+}
+
+impl Execute<Vec<SearchMatch>> for SearchItems<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<Vec<SearchMatch>, AppError> {
+        let pattern = search::compile(self.query, self.mode)?;
+
+        // The index folds case the way the matcher does only for ASCII queries.
+        let indexable = !self.mode.regex && (!self.mode.ignore_case || self.query.is_ascii());
+        let candidates = if indexable { storage.search_candidates(self.query)? } else { None };
+        let ids = match candidates {
+            Some(ids) => ids,
+            None => ListItems.execute(storage)?,
+        };
+
+        let mut matches = Vec::new();
+        for id in ids {
+            let mut matcher = LineMatcher::new(&id, &pattern);
+            match storage.read_item_into(&id, &mut matcher) {
+                Ok(_) => matches.extend(matcher.finish()),
+                // Deleted by another process since it was listed.
+                Err(AppError::ItemNotFound(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::MockStorage;
+
+    fn storage() -> MockStorage {
+        let storage = MockStorage::default();
+        storage.set_content("notes", "Buy milk\nCall Alice\nbuy stamps");
+        storage.set_content("blob", b"milk\0\xff");
+        storage.set_content("todo", "fix bug 42\nship v2");
+        storage
+    }
+
+    fn hits(matches: &[SearchMatch]) -> Vec<(String, usize)> {
+        matches.iter().map(|m| (m.id.clone(), m.line)).collect()
+    }
+
+    #[test]
+    fn substring_search_is_case_sensitive_and_skips_binary_items() {
+        let storage = storage();
+        let command = SearchItems { query: "milk", mode: SearchMode::default() };
+
+        let matches = command.execute(&storage).unwrap();
+
+        assert_eq!(hits(&matches), vec![("notes".to_string(), 1)]);
+        assert_eq!(matches[0].spans, vec![(4, 8)]);
+    }
+
+    #[test]
+    fn ignore_case_search_matches_every_casing() {
+        let storage = storage();
+        let mode = SearchMode { ignore_case: true, regex: false };
+
+        let matches = SearchItems { query: "BUY", mode }.execute(&storage).unwrap();
+
+        assert_eq!(hits(&matches), vec![("notes".to_string(), 1), ("notes".to_string(), 3)]);
+    }
+
+    #[test]
+    fn regex_search_uses_pattern_syntax() {
+        let storage = storage();
+        let mode = SearchMode { ignore_case: false, regex: true };
+
+        let matches = SearchItems { query: r"\d+", mode }.execute(&storage).unwrap();
+
+        assert_eq!(hits(&matches), vec![("todo".to_string(), 1), ("todo".to_string(), 2)]);
+    }
+
+    #[test]
+    fn literal_search_reads_only_index_candidates() {
+        let storage = storage();
+        *storage.candidates_value.borrow_mut() = Some(vec!["todo".to_string(), "gone".to_string()]);
+
+        let literal = SearchItems { query: "v2", mode: SearchMode::default() };
+        assert_eq!(hits(&literal.execute(&storage).unwrap()), vec![("todo".to_string(), 2)]);
+        assert_eq!(storage.get_calls.borrow().as_slice(), ["todo", "gone"]);
+
+        // Patterns cannot be answered from the index, so every item is scanned.
+        let mode = SearchMode { ignore_case: false, regex: true };
+        let matches = SearchItems { query: "milk", mode }.execute(&storage).unwrap();
+        assert_eq!(hits(&matches), vec![("notes".to_string(), 1)]);
+    }
+
+    #[test]
+    fn invalid_pattern_fails_before_reading_items() {
+        let storage = storage();
+        let mode = SearchMode { ignore_case: false, regex: true };
+
+        let result = SearchItems { query: "[", mode }.execute(&storage);
+
+        assert!(matches!(result, Err(AppError::ConfigError(_))));
+        assert!(storage.get_calls.borrow().is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::validate_tags;
+use crate::storage::Storage;
+
+/// Attaches a tag to an existing item, returning `false` if it was already attached.
+pub struct TagItem<'a> {
+    pub id: &'a ItemId,
+    pub tag: &'a str,
+}
+
+impl Execute<bool> for TagItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<bool, AppError> {
+        validate_tags([self.tag])?;
+        storage.add_tag(self.id, self.tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn tag_item_reports_whether_tag_was_new() {
+        let storage = MockStorage::default();
+        storage.set_tags("demo", &["work"]);
+
+        assert!(TagItem { id: &id("demo"), tag: "urgent" }.execute(&storage).unwrap());
+        assert!(!TagItem { id: &id("demo"), tag: "work" }.execute(&storage).unwrap());
+        assert_eq!(storage.tags_value.borrow()["demo"].len(), 2);
+    }
+
+    #[test]
+    fn tag_item_rejects_invalid_tag() {
+        let storage = MockStorage::default();
+        storage.set_tags("demo", &[]);
+
+        let result = TagItem { id: &id("demo"), tag: "not valid" }.execute(&storage);
+        assert!(matches!(result, Err(AppError::ConfigError(_))));
+    }
+
+    #[test]
+    fn tag_item_propagates_not_found() {
+        let storage = MockStorage::default();
+
+        let result = TagItem { id: &id("missing"), tag: "work" }.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
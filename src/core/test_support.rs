@@ -1,12 +1,35 @@
 use crate::error::AppError;
+use crate::id::ItemId;
+use crate::metadata::{ItemMetadata, TrashEntry};
 use crate::storage::Storage;
 use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+/// Parse an identifier known to be valid.
+pub(crate) fn id(raw: &str) -> ItemId {
+    ItemId::parse(raw).expect("test identifiers are valid")
+}
 
 #[derive(Default)]
 pub(crate) struct MockStorage {
-    pub add_calls: RefCell<Vec<(String, String)>>,
+    pub add_calls: RefCell<Vec<(String, Vec<u8>)>>,
+    pub update_calls: RefCell<Vec<(String, Vec<u8>)>>,
+    pub append_calls: RefCell<Vec<(String, Vec<u8>)>>,
+    pub get_calls: RefCell<Vec<String>>,
     pub delete_calls: RefCell<Vec<String>>,
+    pub purge_calls: RefCell<Vec<String>>,
+    pub restore_calls: RefCell<Vec<String>>,
+    pub empty_trash_calls: RefCell<Vec<Option<Duration>>>,
+    pub get_item_value: RefCell<Option<Vec<u8>>>,
+    pub metadata_value: RefCell<Option<ItemMetadata>>,
     pub list_items_values: RefCell<Vec<String>>,
+    pub versions_value: RefCell<Vec<(ItemMetadata, Vec<u8>)>>,
+    pub trash_values: RefCell<Vec<TrashEntry>>,
+    pub tags_value: RefCell<BTreeMap<String, BTreeSet<String>>>,
+    pub contents_value: RefCell<BTreeMap<String, Vec<u8>>>,
+    pub candidates_value: RefCell<Option<Vec<String>>>,
 }
 
 impl MockStorage {
@@ -19,20 +42,214 @@ impl MockStorage {
         values.clear();
         values.extend(items.into_iter().map(Into::into));
     }
+
+    pub fn set_get_item<C: AsRef<[u8]>>(&self, content: C) {
+        *self.get_item_value.borrow_mut() = Some(content.as_ref().to_vec());
+    }
+
+    pub fn set_metadata(&self, metadata: ItemMetadata) {
+        *self.metadata_value.borrow_mut() = Some(metadata);
+    }
+
+    /// Record one retained revision per entry of `contents`, numbered from 1.
+    pub fn set_versions<C: AsRef<[u8]>>(&self, contents: &[C]) {
+        let mut versions = self.versions_value.borrow_mut();
+        versions.clear();
+        for (index, content) in contents.iter().enumerate() {
+            let mut metadata = ItemMetadata::new(content.as_ref(), None);
+            metadata.version = index as u64 + 1;
+            versions.push((metadata, content.as_ref().to_vec()));
+        }
+    }
+
+    /// Store `content` under `id`, registering it as an existing item.
+    pub fn set_content<C: AsRef<[u8]>>(&self, id: &str, content: C) {
+        if !self.contains(id) {
+            self.list_items_values.borrow_mut().push(id.to_string());
+        }
+        self.contents_value.borrow_mut().insert(id.to_string(), content.as_ref().to_vec());
+    }
+
+    /// Attach `tags` to `id`, registering it as an existing item.
+    pub fn set_tags(&self, id: &str, tags: &[&str]) {
+        if !self.contains(id) {
+            self.list_items_values.borrow_mut().push(id.to_string());
+        }
+        let tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self.tags_value.borrow_mut().insert(id.to_string(), tags);
+    }
+
+    /// Put one trash entry per id, each deleted a second after the previous one.
+    pub fn set_trash<I>(&self, ids: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut trash = self.trash_values.borrow_mut();
+        trash.clear();
+        for (index, id) in ids.into_iter().enumerate() {
+            trash.push(TrashEntry { id: id.into(), deleted_at: index as u64 + 1, size: 0 });
+        }
+    }
+
+    fn contains(&self, id: &str) -> bool {
+        self.list_items_values.borrow().iter().any(|existing| existing == id)
+    }
 }
 
 impl Storage for MockStorage {
-    fn add_item(&self, id: &str, content: &str) -> Result<(), AppError> {
-        self.add_calls.borrow_mut().push((id.to_string(), content.to_string()));
+    fn add_item(&self, id: &ItemId, content: &[u8], _: Option<&str>) -> Result<(), AppError> {
+        self.add_calls.borrow_mut().push((id.to_string(), content.to_vec()));
+        if self.contains(id.as_str()) {
+            return Err(AppError::ItemAlreadyExists(id.to_string()));
+        }
+        self.list_items_values.borrow_mut().push(id.to_string());
+        Ok(())
+    }
+
+    fn update_item(&self, id: &ItemId, content: &[u8], _: Option<&str>) -> Result<(), AppError> {
+        self.update_calls.borrow_mut().push((id.to_string(), content.to_vec()));
+        if !self.contains(id.as_str()) {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        self.append_calls.borrow_mut().push((id.to_string(), content.to_vec()));
+        if !self.contains(id.as_str()) {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        self.get_calls.borrow_mut().push(id.to_string());
+        if let Some(content) = self.contents_value.borrow().get(id.as_str()) {
+            return Ok(content.clone());
+        }
+        self.get_item_value.borrow().clone().ok_or_else(|| AppError::ItemNotFound(id.to_string()))
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        let mut metadata = match self.metadata_value.borrow().clone() {
+            Some(metadata) => metadata,
+            None if self.contains(id.as_str()) => ItemMetadata::new(b"", None),
+            None => return Err(AppError::ItemNotFound(id.to_string())),
+        };
+        if let Some(tags) = self.tags_value.borrow().get(id.as_str()) {
+            metadata.tags = tags.clone();
+        }
+        Ok(metadata)
+    }
+
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.add_item(id, &content, content_type)?;
+        self.tags_value.borrow_mut().entry(id.to_string()).or_default().extend(tags.to_vec());
+        Ok(())
+    }
+
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.update_item(id, &content, content_type)?;
+        self.tags_value.borrow_mut().insert(id.to_string(), tags.iter().cloned().collect());
         Ok(())
     }
 
-    fn list_items(&self) -> Result<Vec<String>, AppError> {
-        Ok(self.list_items_values.borrow().clone())
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        if !self.contains(id.as_str()) {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        Ok(self.tags_value.borrow_mut().entry(id.to_string()).or_default().insert(tag.to_string()))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        if !self.contains(id.as_str()) {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        Ok(self.tags_value.borrow_mut().get_mut(id.as_str()).is_some_and(|tags| tags.remove(tag)))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        let versions = self.versions_value.borrow();
+        if versions.is_empty() {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        Ok(versions.iter().map(|(metadata, _)| metadata.clone()).collect())
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        let versions = self.versions_value.borrow();
+        let (_, content) = versions
+            .iter()
+            .find(|(metadata, _)| metadata.version == version)
+            .ok_or_else(|| AppError::VersionNotFound(id.to_string(), version))?;
+        writer.write_all(content)?;
+        Ok(content.len() as u64)
     }
 
-    fn delete_item(&self, id: &str) -> Result<(), AppError> {
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        Ok(self.list_items_values.borrow().iter().map(|raw| id(raw)).collect())
+    }
+
+    fn search_candidates(&self, _query: &str) -> Result<Option<Vec<ItemId>>, AppError> {
+        Ok(self
+            .candidates_value
+            .borrow()
+            .as_ref()
+            .map(|ids| ids.iter().map(|raw| id(raw)).collect()))
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
         self.delete_calls.borrow_mut().push(id.to_string());
         Ok(())
     }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.purge_calls.borrow_mut().push(id.to_string());
+        Ok(())
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        Ok(self.trash_values.borrow().clone())
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.restore_calls.borrow_mut().push(id.to_string());
+        if self.contains(id.as_str()) {
+            return Err(AppError::ItemAlreadyExists(id.to_string()));
+        }
+        let mut trash = self.trash_values.borrow_mut();
+        let index = trash
+            .iter()
+            .rposition(|entry| entry.id == id.as_str())
+            .ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+        trash.remove(index);
+        Ok(())
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        self.empty_trash_calls.borrow_mut().push(older_than);
+        Ok(self.trash_values.borrow_mut().drain(..).collect())
+    }
 }
@@ -0,0 +1,32 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Detaches a tag from an existing item, returning `false` if it was not attached.
+pub struct UntagItem<'a> {
+    pub id: &'a ItemId,
+    pub tag: &'a str,
+}
+
+impl Execute<bool> for UntagItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<bool, AppError> {
+        storage.remove_tag(self.id, self.tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn untag_item_reports_whether_tag_was_attached() {
+        let storage = MockStorage::default();
+        storage.set_tags("demo", &["work"]);
+
+        assert!(UntagItem { id: &id("demo"), tag: "work" }.execute(&storage).unwrap());
+        assert!(!UntagItem { id: &id("demo"), tag: "work" }.execute(&storage).unwrap());
+        assert!(storage.tags_value.borrow()["demo"].is_empty());
+    }
+}
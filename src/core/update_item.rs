@@ -0,0 +1,45 @@
+use crate::core::Execute;
+use crate::error::AppError;
+use crate::id::ItemId;
+use crate::storage::Storage;
+
+/// Example command for replacing the content of an existing item.
+pub struct UpdateItem<'a> {
+    pub id: &'a ItemId,
+    pub content: &'a [u8],
+    /// Replacement content type; `None` keeps the recorded one.
+    pub content_type: Option<&'a str>,
+}
+
+impl Execute<()> for UpdateItem<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<(), AppError> {
+        storage.update_item(self.id, self.content, self.content_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{MockStorage, id};
+
+    #[test]
+    fn update_item_forwards_to_storage() {
+        let storage = MockStorage::default();
+        storage.set_list_items(["demo"]);
+        let command = UpdateItem { id: &id("demo"), content: b"example", content_type: None };
+
+        command.execute(&storage).expect("execution should succeed");
+
+        let calls = storage.update_calls.borrow();
+        assert_eq!(calls.as_slice(), [("demo".to_string(), b"example".to_vec())]);
+    }
+
+    #[test]
+    fn update_item_fails_for_missing_item() {
+        let storage = MockStorage::default();
+        let command = UpdateItem { id: &id("missing"), content: b"example", content_type: None };
+
+        let result = command.execute(&storage);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::id::IdError;
 use std::error::Error;
 use std::fmt::{self, Display};
 use std::io;
@@ -8,16 +9,66 @@ pub enum AppError {
     Io(io::Error),
     /// Configuration or environment issue that prevents command execution.
     ConfigError(String),
+    /// Raised when an identifier breaks one of the [`crate::id::IdRules`].
+    InvalidItemId(String, IdError),
     /// Raised when a requested item cannot be located in storage.
     ItemNotFound(String),
+    /// Raised when a requested revision of an item is not retained in its history.
+    VersionNotFound(String, u64),
+    /// Raised when creating an item whose identifier is already taken.
+    ItemAlreadyExists(String),
+    /// Raised when an identifier differs only in letter case from an existing item or namespace,
+    /// which would share its directory on case-insensitive filesystems.
+    CaseCollision(String, String),
+    /// Raised when binary content would be written somewhere only text belongs.
+    BinaryContent(String),
+    /// Raised when the interactive editor cannot be run or produces no content.
+    EditorFailed(String),
+    /// Raised when another process holds a storage lock for longer than the configured timeout.
+    LockTimeout(String),
+    /// Raised when the search index disagrees with item content: missing and stale postings.
+    IndexOutOfDate(usize, usize),
+    /// Raised when `doctor` finds problems in the store, after reporting them.
+    ProblemsFound(usize),
 }
 
 impl Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AppError::Io(err) => write!(f, "{}", err),
-            AppError::ConfigError(message) => write!(f, "{message}"),
+            AppError::BinaryContent(id) => write!(
+                f,
+                "Item '{id}' contains binary data; refusing to print it to a terminal (use --raw)"
+            ),
+            AppError::ConfigError(message) | AppError::EditorFailed(message) => {
+                write!(f, "{message}")
+            }
+            AppError::InvalidItemId(id, reason) => {
+                write!(f, "invalid item identifier '{id}': {reason}")
+            }
             AppError::ItemNotFound(id) => write!(f, "Item '{id}' was not found"),
+            AppError::VersionNotFound(id, version) => {
+                write!(f, "Item '{id}' has no version {version} (see `history {id}`)")
+            }
+            AppError::ItemAlreadyExists(id) => {
+                write!(f, "Item '{id}' already exists (use --force to overwrite)")
+            }
+            AppError::CaseCollision(id, existing) => write!(
+                f,
+                "Item '{id}' differs only in letter case from '{existing}'; both would share one \
+                 directory on case-insensitive filesystems"
+            ),
+            AppError::LockTimeout(resource) => write!(
+                f,
+                "Timed out waiting for the lock on {resource}; another process is still using it"
+            ),
+            AppError::IndexOutOfDate(missing, stale) => write!(
+                f,
+                "Search index is out of date: {missing} missing and {stale} stale entries \
+                 (run `index rebuild`)"
+            ),
+            AppError::ProblemsFound(1) => write!(f, "Found 1 problem in the store"),
+            AppError::ProblemsFound(count) => write!(f, "Found {count} problems in the store"),
         }
     }
 }
@@ -26,7 +77,17 @@ impl Error for AppError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             AppError::Io(err) => Some(err),
-            AppError::ConfigError(_) | AppError::ItemNotFound(_) => None,
+            AppError::ConfigError(_)
+            | AppError::InvalidItemId(..)
+            | AppError::ItemNotFound(_)
+            | AppError::VersionNotFound(..)
+            | AppError::ItemAlreadyExists(_)
+            | AppError::CaseCollision(..)
+            | AppError::BinaryContent(_)
+            | AppError::EditorFailed(_)
+            | AppError::LockTimeout(_)
+            | AppError::IndexOutOfDate(..)
+            | AppError::ProblemsFound(_) => None,
         }
     }
 }
@@ -37,6 +98,19 @@ impl From<io::Error> for AppError {
     }
 }
 
+/// SQLite reports a busy database once its busy timeout, the configured lock timeout, expires.
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for AppError {
+    fn from(value: rusqlite::Error) -> Self {
+        match value.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                AppError::LockTimeout("the SQLite database".to_string())
+            }
+            _ => AppError::Io(io::Error::other(value)),
+        }
+    }
+}
+
 impl AppError {
     pub(crate) fn config_error<S: Into<String>>(message: S) -> Self {
         AppError::ConfigError(message.into())
@@ -46,8 +120,33 @@ impl AppError {
     pub fn kind(&self) -> io::ErrorKind {
         match self {
             AppError::Io(err) => err.kind(),
-            AppError::ConfigError(_) => io::ErrorKind::InvalidInput,
-            AppError::ItemNotFound(_) => io::ErrorKind::NotFound,
+            AppError::ConfigError(_) | AppError::InvalidItemId(..) => io::ErrorKind::InvalidInput,
+            AppError::ItemNotFound(_) | AppError::VersionNotFound(..) => io::ErrorKind::NotFound,
+            AppError::ItemAlreadyExists(_) | AppError::CaseCollision(..) => {
+                io::ErrorKind::AlreadyExists
+            }
+            AppError::BinaryContent(_) => io::ErrorKind::InvalidData,
+            AppError::EditorFailed(_) => io::ErrorKind::Other,
+            AppError::LockTimeout(_) => io::ErrorKind::TimedOut,
+            AppError::IndexOutOfDate(..) | AppError::ProblemsFound(_) => io::ErrorKind::InvalidData,
+        }
+    }
+
+    /// Stable machine-readable identifier for the error category.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Io(_) => "io",
+            AppError::ConfigError(_) => "config",
+            AppError::InvalidItemId(..) => "invalid_item_id",
+            AppError::ItemNotFound(_) => "item_not_found",
+            AppError::VersionNotFound(..) => "version_not_found",
+            AppError::ItemAlreadyExists(_) => "item_already_exists",
+            AppError::CaseCollision(..) => "case_collision",
+            AppError::BinaryContent(_) => "binary_content",
+            AppError::EditorFailed(_) => "editor_failed",
+            AppError::LockTimeout(_) => "lock_timeout",
+            AppError::IndexOutOfDate(..) => "index_out_of_date",
+            AppError::ProblemsFound(_) => "problems_found",
         }
     }
 }
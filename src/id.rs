@@ -0,0 +1,323 @@
+//! Item identifiers and the rules they are checked against.
+//!
+//! With the default [`IdRules`] an identifier follows this grammar:
+//!
+//! ```text
+//! id      = segment *( "/" segment )      ; at most `max_length` bytes in total
+//! segment = first *( first / "." )
+//! first   = ASCII letter / ASCII digit / "-" / "_"
+//! ```
+//!
+//! Segments name nested directories, so a leading `.` is reserved for the store's own entries
+//! (`.trash`, `.index`, scratch files) and also rules out `.` and `..`. Names Windows reserves for
+//! devices, such as `con` or `nul.txt`, are rejected on every platform so stores stay portable.
+//!
+//! [`IdCharset::Unicode`] additionally accepts any Unicode letter or digit; identifiers are then
+//! normalized to NFC first, so composed and decomposed spellings name the same item.
+//!
+//! Segments differing only in letter case, such as `Foo` and `foo`, share a directory on
+//! case-insensitive filesystems. Storage refuses to create one next to the other, and
+//! [`IdRules::fold_case`] lowercases every identifier so they always name the same item.
+
+use crate::error::AppError;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::ops::Deref;
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+/// Separator between the namespace segments of an identifier.
+pub const SEPARATOR: char = '/';
+
+/// Longest identifier accepted unless configured otherwise, in bytes.
+pub const DEFAULT_MAX_ID_LENGTH: usize = 128;
+
+/// Device names Windows refuses as file names, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Validated identifier of a stored item, such as `team/service/token`.
+///
+/// Only [`IdRules::parse`] (or [`ItemId::parse`] for the default rules) creates one, so every
+/// `ItemId` maps to a safe relative path below the storage root.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct ItemId(String);
+
+impl ItemId {
+    /// Check `raw` against the default [`IdRules`].
+    pub fn parse(raw: &str) -> Result<Self, AppError> {
+        IdRules::default().parse(raw)
+    }
+
+    /// Rebuild an identifier read back from a backend, or `None` if no rules could have
+    /// produced it, such as a directory created by hand.
+    pub(crate) fn from_stored(raw: &str) -> Option<Self> {
+        let rules =
+            IdRules { charset: IdCharset::Unicode, max_length: usize::MAX, fold_case: false };
+        rules.check(raw).ok().filter(|id| id.0 == raw)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Enclosing namespaces, outermost first: `a` and `a/b` for `a/b/c`.
+    pub fn namespaces(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.0.match_indices(SEPARATOR).map(|(end, _)| &self.0[..end])
+    }
+}
+
+impl Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Deref for ItemId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ItemId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for ItemId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ItemId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl From<ItemId> for String {
+    fn from(id: ItemId) -> Self {
+        id.0
+    }
+}
+
+impl FromStr for ItemId {
+    type Err = AppError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Self::parse(raw)
+    }
+}
+
+/// Characters accepted in identifier segments besides `-`, `_` and `.`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdCharset {
+    /// ASCII letters and digits only, so look-alike letters from other scripts are refused.
+    #[default]
+    Ascii,
+    /// Any Unicode letter or digit, normalized to NFC.
+    Unicode,
+}
+
+/// Policy identifiers are checked against before they reach a storage backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRules {
+    pub charset: IdCharset,
+    /// Longest accepted identifier in bytes, after normalization.
+    pub max_length: usize,
+    /// Lowercase identifiers, so spellings differing only in case name the same item.
+    pub fold_case: bool,
+}
+
+impl Default for IdRules {
+    fn default() -> Self {
+        Self { charset: IdCharset::default(), max_length: DEFAULT_MAX_ID_LENGTH, fold_case: false }
+    }
+}
+
+impl IdRules {
+    /// Validate and normalize `raw`, failing with [`AppError::InvalidItemId`] naming the rule it
+    /// breaks.
+    pub fn parse(&self, raw: &str) -> Result<ItemId, AppError> {
+        self.check(raw).map_err(|reason| AppError::InvalidItemId(raw.to_string(), reason))
+    }
+
+    fn check(&self, raw: &str) -> Result<ItemId, IdError> {
+        let mut id: String = match self.charset {
+            IdCharset::Ascii => raw.to_string(),
+            IdCharset::Unicode => raw.nfc().collect(),
+        };
+        if self.fold_case {
+            id = id.to_lowercase();
+        }
+        if id.is_empty() {
+            return Err(IdError::Empty);
+        }
+        if id.len() > self.max_length {
+            return Err(IdError::TooLong { length: id.len(), max: self.max_length });
+        }
+
+        for segment in id.split(SEPARATOR) {
+            if segment.is_empty() {
+                return Err(IdError::EmptySegment);
+            }
+            if segment.starts_with('.') {
+                return Err(IdError::HiddenSegment(segment.to_string()));
+            }
+            let stem = segment.split('.').next().unwrap_or(segment).to_ascii_lowercase();
+            if RESERVED_NAMES.contains(&stem.as_str()) {
+                return Err(IdError::ReservedName(segment.to_string()));
+            }
+            for c in segment.chars() {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    continue;
+                }
+                if c.is_alphanumeric() {
+                    if self.charset == IdCharset::Unicode {
+                        continue;
+                    }
+                    return Err(IdError::NonAscii(c));
+                }
+                return Err(IdError::InvalidChar(c));
+            }
+        }
+        Ok(ItemId(id))
+    }
+}
+
+/// The identifier rule a rejected identifier breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    Empty,
+    TooLong {
+        length: usize,
+        max: usize,
+    },
+    /// A leading, trailing or doubled `/`.
+    EmptySegment,
+    HiddenSegment(String),
+    ReservedName(String),
+    /// A letter or digit outside ASCII while the ASCII charset is in effect.
+    NonAscii(char),
+    InvalidChar(char),
+}
+
+impl Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::Empty => write!(f, "identifiers must not be empty"),
+            IdError::TooLong { length, max } => {
+                write!(
+                    f,
+                    "it is {length} bytes long, more than the limit of {max} (`id_max_length`)"
+                )
+            }
+            IdError::EmptySegment => {
+                write!(
+                    f,
+                    "namespace segments must not be empty (check for a leading, trailing or doubled '/')"
+                )
+            }
+            IdError::HiddenSegment(segment) => write!(
+                f,
+                "segment '{segment}' starts with '.', which is reserved for the store's own entries"
+            ),
+            IdError::ReservedName(segment) => {
+                write!(f, "segment '{segment}' is a device name reserved by Windows")
+            }
+            IdError::NonAscii(c) => write!(
+                f,
+                "'{c}' (U+{:04X}) is not an ASCII letter or digit; set `id_charset` to `unicode` \
+                 to allow it",
+                *c as u32
+            ),
+            IdError::InvalidChar(c) => write!(
+                f,
+                "'{}' is not allowed; use letters, digits, '-', '_', '.' and '/' between namespaces",
+                c.escape_debug()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(rules: IdRules, raw: &str) -> IdError {
+        rules.check(raw).expect_err("identifier should be rejected")
+    }
+
+    #[test]
+    fn parse_accepts_the_documented_grammar() {
+        for raw in ["demo", "v1.2_rc-3", "team/service/token", "a/b.c/d_e", "config.json"] {
+            assert_eq!(ItemId::parse(raw).unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn parse_names_the_rule_that_failed() {
+        let rules = IdRules::default();
+
+        assert_eq!(reason(rules, ""), IdError::Empty);
+        assert_eq!(reason(rules, "team//token"), IdError::EmptySegment);
+        assert_eq!(reason(rules, "/etc/passwd"), IdError::EmptySegment);
+        assert_eq!(reason(rules, "team/"), IdError::EmptySegment);
+        assert_eq!(reason(rules, "../escape"), IdError::HiddenSegment("..".into()));
+        assert_eq!(reason(rules, ".trash"), IdError::HiddenSegment(".trash".into()));
+        assert_eq!(reason(rules, "team/NUL.txt"), IdError::ReservedName("NUL.txt".into()));
+        assert_eq!(reason(rules, "has space"), IdError::InvalidChar(' '));
+        assert_eq!(reason(rules, "a\\b"), IdError::InvalidChar('\\'));
+        assert_eq!(reason(rules, &"x".repeat(129)), IdError::TooLong { length: 129, max: 128 });
+
+        let err = ItemId::parse("team/.hidden").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid item identifier 'team/.hidden': segment '.hidden' starts with '.', which is \
+             reserved for the store's own entries"
+        );
+    }
+
+    #[test]
+    fn ascii_charset_rejects_look_alike_letters() {
+        // Cyrillic 'а' renders like the ASCII 'a'.
+        assert_eq!(reason(IdRules::default(), "p\u{430}ypal"), IdError::NonAscii('\u{430}'));
+    }
+
+    #[test]
+    fn unicode_charset_normalizes_to_nfc() {
+        let rules = IdRules { charset: IdCharset::Unicode, ..IdRules::default() };
+
+        let decomposed = rules.parse("cafe\u{301}").unwrap();
+        assert_eq!(decomposed, "caf\u{e9}");
+        assert_eq!(decomposed, rules.parse("caf\u{e9}").unwrap());
+        assert_eq!(reason(rules, "caf\u{e9} au lait"), IdError::InvalidChar(' '));
+        assert!(ItemId::from_stored("caf\u{e9}").is_some());
+        assert!(ItemId::from_stored("cafe\u{301}").is_none());
+    }
+
+    #[test]
+    fn fold_case_lowercases_identifiers() {
+        let rules = IdRules { fold_case: true, ..IdRules::default() };
+
+        assert_eq!(rules.parse("Team/ReadMe").unwrap(), "team/readme");
+        assert_eq!(IdRules::default().parse("Team/ReadMe").unwrap(), "Team/ReadMe");
+    }
+
+    #[test]
+    fn namespaces_lists_enclosing_prefixes() {
+        let id = ItemId::parse("a/b/c").unwrap();
+
+        assert_eq!(id.namespaces().collect::<Vec<_>>(), ["a", "a/b"]);
+        assert_eq!(ItemId::parse("solo").unwrap().namespaces().count(), 0);
+    }
+}
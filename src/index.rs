@@ -0,0 +1,341 @@
+//! On-disk inverted index mapping content trigrams to the items containing them.
+//!
+//! Terms are the overlapping three-character windows of every alphanumeric run in an item,
+//! lowercased, so any item containing a literal query also contains all of the query's terms.
+//! Postings are spread over [`SHARD_COUNT`] shard files holding one entry per line:
+//!
+//! - `=<term>\t<id> <id>…` lists the items of a term as of the last rebuild;
+//! - `+<term>\t<id>` and `-<term>\t<id>` record later additions and removals, in order.
+//!
+//! Writers append additions before the content they describe becomes visible and removals only
+//! afterwards, so the index may list items that no longer match but never misses one that does.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Number of shard files postings are spread over.
+const SHARD_COUNT: u32 = 256;
+
+/// Length of an indexed term in characters.
+const TERM_LEN: usize = 3;
+
+/// Summary of an index rebuild or verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct IndexReport {
+    /// Text items covered by the index.
+    pub items: usize,
+    /// Distinct terms across those items.
+    pub terms: usize,
+    /// Postings an item's content calls for but the index lacks.
+    pub missing: usize,
+    /// Postings the index holds that no item's content calls for.
+    pub stale: usize,
+}
+
+/// Handle on an index directory; the index is enabled only while the directory exists.
+#[derive(Debug, Clone)]
+pub(crate) struct SearchIndex {
+    dir: PathBuf,
+}
+
+impl SearchIndex {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub(crate) fn exists(&self) -> bool {
+        self.dir.is_dir()
+    }
+
+    /// Append postings recording that `id` gained the `added` terms and lost the `removed` ones.
+    pub(crate) fn record(
+        &self,
+        id: &str,
+        added: &BTreeSet<String>,
+        removed: &BTreeSet<String>,
+    ) -> io::Result<()> {
+        let mut shards: BTreeMap<u32, String> = BTreeMap::new();
+        let changes = added.iter().map(|term| ('+', term)).chain(removed.iter().map(|t| ('-', t)));
+        for (op, term) in changes {
+            shards.entry(shard_of(term)).or_default().push_str(&format!("{op}{term}\t{id}\n"));
+        }
+        for (shard, lines) in shards {
+            let path = shard_path(&self.dir, shard);
+            OpenOptions::new().create(true).append(true).open(path)?.write_all(lines.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Items listed under every one of `terms`.
+    pub(crate) fn lookup(&self, terms: &BTreeSet<String>) -> io::Result<BTreeSet<String>> {
+        let mut wanted: BTreeMap<u32, BTreeSet<&str>> = BTreeMap::new();
+        for term in terms {
+            wanted.entry(shard_of(term)).or_default().insert(term);
+        }
+
+        let mut postings: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (shard, terms) in wanted {
+            for_each_entry(&shard_path(&self.dir, shard), |op, term, ids| {
+                if terms.contains(term) {
+                    apply(postings.entry(term.to_string()).or_default(), op, ids);
+                }
+            })?;
+        }
+
+        let mut sets = terms.iter().map(|term| postings.remove(term).unwrap_or_default());
+        let first = sets.next().unwrap_or_default();
+        Ok(sets.fold(first, |acc, set| acc.intersection(&set).cloned().collect()))
+    }
+
+    /// Write compacted shards for `postings` into `dir`, which must already exist.
+    pub(crate) fn write_shards(&self, dir: &Path, postings: &Postings) -> io::Result<()> {
+        let mut shards: BTreeMap<u32, String> = BTreeMap::new();
+        for (term, docs) in &postings.terms {
+            let ids: Vec<&str> = docs.iter().map(|&doc| postings.ids[doc].as_str()).collect();
+            let line = format!("={term}\t{}\n", ids.join(" "));
+            shards.entry(shard_of(term)).or_default().push_str(&line);
+        }
+        for (shard, lines) in shards {
+            let mut file = File::create_new(shard_path(dir, shard))?;
+            file.write_all(lines.as_bytes())?;
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Compare the index against the postings its items' content calls for.
+    pub(crate) fn verify(&self, expected: &Postings) -> io::Result<IndexReport> {
+        let mut by_shard: BTreeMap<u32, Vec<&String>> = BTreeMap::new();
+        for term in expected.terms.keys() {
+            by_shard.entry(shard_of(term)).or_default().push(term);
+        }
+
+        let mut report = IndexReport {
+            items: expected.ids.len(),
+            terms: expected.terms.len(),
+            ..Default::default()
+        };
+        for shard in 0..SHARD_COUNT {
+            let mut actual: HashMap<String, BTreeSet<String>> = HashMap::new();
+            for_each_entry(&shard_path(&self.dir, shard), |op, term, ids| {
+                apply(actual.entry(term.to_string()).or_default(), op, ids);
+            })?;
+
+            for term in by_shard.remove(&shard).unwrap_or_default() {
+                let found = actual.remove(term.as_str()).unwrap_or_default();
+                let wanted: BTreeSet<&str> =
+                    expected.terms[term].iter().map(|&doc| expected.ids[doc].as_str()).collect();
+                report.missing += wanted.iter().filter(|id| !found.contains(**id)).count();
+                report.stale += found.iter().filter(|id| !wanted.contains(id.as_str())).count();
+            }
+            report.stale += actual.values().map(BTreeSet::len).sum::<usize>();
+        }
+        Ok(report)
+    }
+}
+
+/// Terms of every item in a store, keyed by term with items referenced by position in `ids`.
+#[derive(Debug, Default)]
+pub(crate) struct Postings {
+    pub ids: Vec<String>,
+    pub terms: BTreeMap<String, Vec<usize>>,
+}
+
+impl Postings {
+    pub(crate) fn insert(&mut self, id: &str, terms: BTreeSet<String>) {
+        let doc = self.ids.len();
+        self.ids.push(id.to_string());
+        for term in terms {
+            self.terms.entry(term).or_default().push(doc);
+        }
+    }
+}
+
+/// Writer collecting the terms of the text streamed through it.
+///
+/// Binary input, containing NUL bytes or invalid UTF-8, has no terms.
+#[derive(Debug, Default)]
+pub(crate) struct TermWriter {
+    terms: BTreeSet<String>,
+    window: Vec<char>,
+    pending: Vec<u8>,
+    binary: bool,
+}
+
+impl TermWriter {
+    /// The collected terms, or `None` if the input turned out to be binary.
+    pub(crate) fn finish(self) -> Option<BTreeSet<String>> {
+        (!self.binary && self.pending.is_empty()).then_some(self.terms)
+    }
+
+    fn feed(&mut self, text: &str) {
+        for c in text.chars() {
+            if !c.is_alphanumeric() {
+                self.window.clear();
+                continue;
+            }
+            // The long s matches `s` case-insensitively, yet it is already lowercase.
+            let folded = if c == 'ſ' { 's' } else { c };
+            for lower in folded.to_lowercase() {
+                if self.window.len() == TERM_LEN {
+                    self.window.remove(0);
+                }
+                self.window.push(lower);
+                if self.window.len() == TERM_LEN {
+                    self.terms.insert(self.window.iter().collect());
+                }
+            }
+        }
+    }
+}
+
+impl Write for TermWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.binary {
+            return Ok(buf.len());
+        }
+        if buf.contains(&0) {
+            self.binary = true;
+            return Ok(buf.len());
+        }
+
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // A multi-byte character may continue in the next write.
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => {
+                self.binary = true;
+                return Ok(buf.len());
+            }
+        };
+        let rest = self.pending.split_off(valid);
+        let text = std::mem::replace(&mut self.pending, rest);
+        self.feed(std::str::from_utf8(&text).unwrap_or_default());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Terms of a literal query; items containing the query contain all of them.
+pub(crate) fn terms_of(text: &str) -> BTreeSet<String> {
+    let mut writer = TermWriter::default();
+    writer.feed(text);
+    writer.terms
+}
+
+/// Terms of the file at `path`, or `None` if it holds binary content.
+pub(crate) fn terms_of_file(path: &Path) -> io::Result<Option<BTreeSet<String>>> {
+    let mut writer = TermWriter::default();
+    io::copy(&mut File::open(path)?, &mut writer)?;
+    Ok(writer.finish())
+}
+
+/// Call `visit` with each well-formed entry of a shard file; a missing shard has no entries.
+fn for_each_entry(path: &Path, mut visit: impl FnMut(char, &str, &str)) -> io::Result<()> {
+    let mut content = String::new();
+    match File::open(path) {
+        Ok(mut file) => file.read_to_string(&mut content)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for line in content.lines() {
+        let mut chars = line.chars();
+        if let Some(op @ ('=' | '+' | '-')) = chars.next()
+            && let Some((term, ids)) = chars.as_str().split_once('\t')
+        {
+            visit(op, term, ids);
+        }
+    }
+    Ok(())
+}
+
+fn apply(set: &mut BTreeSet<String>, op: char, ids: &str) {
+    for id in ids.split(' ').filter(|id| !id.is_empty()) {
+        if op == '-' {
+            set.remove(id);
+        } else {
+            set.insert(id.to_string());
+        }
+    }
+}
+
+fn shard_path(dir: &Path, shard: u32) -> PathBuf {
+    dir.join(format!("{shard:02x}.log"))
+}
+
+/// FNV-1a hash of `term`, reduced to a shard number.
+fn shard_of(term: &str) -> u32 {
+    let hash = term
+        .bytes()
+        .fold(0x811c_9dc5_u32, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+    hash % SHARD_COUNT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn set(terms: &[&str]) -> BTreeSet<String> {
+        terms.iter().map(|term| term.to_string()).collect()
+    }
+
+    #[test]
+    fn terms_are_lowercase_trigrams_of_words() {
+        assert_eq!(terms_of("Milk, eggs"), set(&["egg", "ggs", "ilk", "mil"]));
+        assert!(terms_of("a bc").is_empty());
+        assert_eq!(terms_of("ſun"), terms_of("SUN"));
+    }
+
+    #[test]
+    fn term_writer_handles_split_characters_and_binary_input() {
+        let mut writer = TermWriter::default();
+        let text = "café".as_bytes();
+        writer.write_all(&text[..4]).unwrap();
+        writer.write_all(&text[4..]).unwrap();
+        assert_eq!(writer.finish(), Some(set(&["caf", "afé"])));
+
+        let mut writer = TermWriter::default();
+        writer.write_all(b"text\0").unwrap();
+        assert_eq!(writer.finish(), None);
+    }
+
+    #[test]
+    fn lookup_replays_appended_postings() {
+        let dir = TempDir::new().unwrap();
+        let index = SearchIndex::new(dir.path().to_path_buf());
+
+        index.record("one", &set(&["abc", "bcd"]), &BTreeSet::new()).unwrap();
+        index.record("two", &set(&["abc"]), &BTreeSet::new()).unwrap();
+        assert_eq!(index.lookup(&set(&["abc"])).unwrap(), set(&["one", "two"]));
+        assert_eq!(index.lookup(&set(&["abc", "bcd"])).unwrap(), set(&["one"]));
+
+        index.record("one", &BTreeSet::new(), &set(&["abc"])).unwrap();
+        assert_eq!(index.lookup(&set(&["abc"])).unwrap(), set(&["two"]));
+        assert!(index.lookup(&set(&["zzz"])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn verify_counts_missing_and_stale_postings() {
+        let dir = TempDir::new().unwrap();
+        let index = SearchIndex::new(dir.path().to_path_buf());
+        let mut expected = Postings::default();
+        expected.insert("one", set(&["abc", "bcd"]));
+        index.write_shards(dir.path(), &expected).unwrap();
+
+        let report = index.verify(&expected).unwrap();
+        assert_eq!((report.items, report.terms, report.missing, report.stale), (1, 2, 0, 0));
+
+        index.record("one", &set(&["xyz"]), &set(&["abc"])).unwrap();
+        let report = index.verify(&expected).unwrap();
+        assert_eq!((report.missing, report.stale), (1, 1));
+    }
+}
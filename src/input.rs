@@ -0,0 +1,224 @@
+use crate::error::AppError;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Editor used when neither the config nor the environment names one.
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// Where a command reads item content from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentSource {
+    /// Content given directly on the command line.
+    Inline(String),
+    /// Content read from a file.
+    File(PathBuf),
+    /// Content read from standard input until EOF.
+    Stdin,
+    /// Content composed in an interactive editor session.
+    Editor,
+}
+
+impl ContentSource {
+    /// Choose a source from command-line arguments.
+    ///
+    /// `--content -` and a non-terminal stdin both read from stdin; with no arguments on an
+    /// interactive terminal the content is composed in the editor.
+    pub fn resolve(
+        content: Option<String>,
+        from_file: Option<PathBuf>,
+        stdin_is_terminal: bool,
+    ) -> Self {
+        match (content, from_file) {
+            (Some(content), _) if content == "-" => ContentSource::Stdin,
+            (Some(content), _) => ContentSource::Inline(content),
+            (None, Some(path)) => ContentSource::File(path),
+            (None, None) if !stdin_is_terminal => ContentSource::Stdin,
+            (None, None) => ContentSource::Editor,
+        }
+    }
+
+    /// Read the content, launching `editor` for [`ContentSource::Editor`].
+    ///
+    /// Files and stdin are read as raw bytes so binary payloads pass through untouched.
+    pub fn read(self, editor: &str) -> Result<Vec<u8>, AppError> {
+        match self {
+            ContentSource::Inline(content) => Ok(content.into_bytes()),
+            ContentSource::File(path) => Ok(fs::read(path)?),
+            ContentSource::Stdin => {
+                let mut content = Vec::new();
+                io::stdin().lock().read_to_end(&mut content)?;
+                Ok(content)
+            }
+            ContentSource::Editor => {
+                let content = edit_text(editor, "")?;
+                if content.trim().is_empty() {
+                    return Err(AppError::EditorFailed(
+                        "editor produced empty content; aborting".to_string(),
+                    ));
+                }
+                Ok(content.into_bytes())
+            }
+        }
+    }
+
+    /// Open the content as a stream so files and stdin are never held in memory whole.
+    ///
+    /// Inline and editor content is small by nature and is buffered before being wrapped.
+    pub fn open(self, editor: &str) -> Result<Box<dyn Read>, AppError> {
+        match self {
+            ContentSource::File(path) => Ok(Box::new(File::open(path)?)),
+            ContentSource::Stdin => Ok(Box::new(io::stdin())),
+            source => Ok(Box::new(Cursor::new(source.read(editor)?))),
+        }
+    }
+}
+
+/// Open `initial` in `editor` and return the saved text.
+///
+/// `editor` may contain arguments (e.g. `code --wait`); the temporary file path is appended.
+pub fn edit_text(editor: &str, initial: &str) -> Result<String, AppError> {
+    let mut words = editor.split_whitespace();
+    let program =
+        words.next().ok_or_else(|| AppError::EditorFailed("no editor configured".to_string()))?;
+
+    let file = TempFile::create(initial)?;
+    let status = Command::new(program).args(words).arg(file.path()).status().map_err(|err| {
+        AppError::EditorFailed(format!("failed to launch editor '{program}': {err}"))
+    })?;
+    if !status.success() {
+        return Err(AppError::EditorFailed(format!("editor '{program}' exited with {status}")));
+    }
+
+    Ok(fs::read_to_string(file.path())?)
+}
+
+/// Scratch file handed to the editor, removed with its directory when dropped.
+///
+/// The content is often a secret, so the file is created fresh, readable only by its owner,
+/// inside a directory only its owner can enter; nothing planted in the shared temp directory can
+/// read it or redirect the write.
+struct TempFile {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn create(content: &str) -> Result<Self, AppError> {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("rs-cli-tmpl-");
+        #[cfg(unix)]
+        builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+        let dir = builder.tempdir()?;
+        let path = dir.path().join("content.txt");
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path)?;
+        file.write_all(content.as_bytes())?;
+        Ok(Self { _dir: dir, path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_prefers_explicit_arguments() {
+        assert_eq!(
+            ContentSource::resolve(Some("value".to_string()), None, true),
+            ContentSource::Inline("value".to_string())
+        );
+        assert_eq!(ContentSource::resolve(Some("-".to_string()), None, true), ContentSource::Stdin);
+        assert_eq!(
+            ContentSource::resolve(None, Some(PathBuf::from("item.txt")), false),
+            ContentSource::File(PathBuf::from("item.txt"))
+        );
+    }
+
+    #[test]
+    fn resolve_falls_back_to_stdin_or_editor() {
+        assert_eq!(ContentSource::resolve(None, None, false), ContentSource::Stdin);
+        assert_eq!(ContentSource::resolve(None, None, true), ContentSource::Editor);
+    }
+
+    #[test]
+    fn read_loads_file_contents() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("payload.txt");
+        fs::write(&path, "line one\nline two\n").unwrap();
+
+        let content = ContentSource::File(path).read(DEFAULT_EDITOR).unwrap();
+        assert_eq!(content, b"line one\nline two\n");
+    }
+
+    #[test]
+    fn open_streams_file_contents() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("payload.bin");
+        fs::write(&path, [0u8, 1, 2, 255]).unwrap();
+
+        let mut content = Vec::new();
+        ContentSource::File(path).open(DEFAULT_EDITOR).unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(content, [0, 1, 2, 255]);
+
+        let mut inline = String::new();
+        ContentSource::Inline("text".to_string())
+            .open(DEFAULT_EDITOR)
+            .unwrap()
+            .read_to_string(&mut inline)
+            .unwrap();
+        assert_eq!(inline, "text");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn edit_text_returns_saved_editor_content() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let script = dir.path().join("editor.sh");
+        fs::write(&script, "#!/bin/sh\nprintf 'final: ' > \"$1.new\"\ncat \"$1\" >> \"$1.new\"\nmv \"$1.new\" \"$1\"\n")
+            .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let content = edit_text(script.to_str().unwrap(), "a draft note").unwrap();
+        assert_eq!(content, "final: a draft note");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_scratch_file_is_private_and_removed_afterwards() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = TempFile::create("secret").unwrap();
+        let path = file.path().to_path_buf();
+        let dir = path.parent().unwrap().to_path_buf();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+
+        drop(file);
+        assert!(!dir.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_failures_and_empty_content_are_reported() {
+        let result = edit_text("false", "");
+        assert!(matches!(result, Err(AppError::EditorFailed(_))));
+
+        let result = ContentSource::Editor.read("true");
+        assert!(
+            matches!(result, Err(AppError::EditorFailed(ref message)) if message.contains("empty"))
+        );
+    }
+}
@@ -1,9 +1,59 @@
 //! Library entry point exposing the core command handlers.
+//!
+//! The public extension points are the [`Storage`] trait, for new backends, and the [`Execute`]
+//! trait, for new commands. Every function in [`commands`] that does not need a particular
+//! backend accepts any `&impl Storage`.
+//!
+//! The stable API is [`commands`], [`core`] and the items re-exported here; the modules behind
+//! them are private so helpers and on-disk formats can change without a major release.
 
 pub mod commands;
-pub mod error;
+#[cfg(feature = "testing")]
+pub mod conformance;
+pub mod core;
 
-mod core;
-mod storage;
+pub(crate) mod config;
+pub(crate) mod error;
+pub(crate) mod id;
+pub(crate) mod index;
+pub(crate) mod input;
+pub(crate) mod logfile;
+pub(crate) mod memory;
+pub(crate) mod metadata;
+pub(crate) mod output;
+pub(crate) mod search;
+#[cfg(feature = "sqlite")]
+pub(crate) mod sqlite;
+pub(crate) mod storage;
 
-pub use commands::{add, delete, list};
+pub use commands::{
+    AddOptions, DeleteOptions, ListOptions, ShowOptions, add, add_from_reader, append, delete,
+    list, show, update,
+};
+pub use config::Source as ConfigSource;
+pub use core::Execute;
+pub use error::AppError;
+pub use id::{IdCharset, IdError, IdRules, ItemId};
+pub use index::IndexReport;
+pub use logfile::{CompactReport, LogFileStorage};
+pub use memory::MemoryStorage;
+pub use metadata::{ItemMetadata, TrashEntry};
+pub use output::{
+    CaptureReporter, ConfigEntry, Event, Finding, ListEntry, Reporter, SilentReporter,
+};
+pub use search::{SearchMatch, SearchMode};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+pub use storage::{FilesystemStorage, Storage};
+
+/// Pieces the `rs-cli-tmpl` binary is assembled from; not part of the stable API, like the
+/// `config_*` functions in [`commands`] that take them.
+#[doc(hidden)]
+pub mod cli {
+    pub use crate::config::{Backend, ColorMode, Config, KEYS, Settings, config_path};
+    pub use crate::input::{ContentSource, DEFAULT_EDITOR};
+    pub use crate::logfile::LOG_FILE_NAME;
+    pub use crate::output::{OutputFormat, StdoutReporter};
+    #[cfg(feature = "sqlite")]
+    pub use crate::sqlite::DATABASE_FILE_NAME;
+}
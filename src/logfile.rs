@@ -0,0 +1,1007 @@
+//! Storage backend keeping the whole store in one append-only log file.
+//!
+//! The file starts with a header naming the format version and a generation, bumped whenever
+//! [`LogFileStorage::compact`] rewrites the file. Every change is then appended as one record:
+//!
+//! ```text
+//! payload length (u64 LE) | CRC-32 of payload (u32 LE) | payload
+//! payload = header length (u32 LE) | JSON header | item content
+//! ```
+//!
+//! Nothing is rewritten in place, so superseded content stays in the file (it also backs the
+//! item history) until `compact` copies only what the store still refers to. The index of
+//! where each revision lives is rebuilt by replaying the log on open. A last record that is cut
+//! short or fails its checksum, as left by a write interrupted by a crash, is ignored and
+//! truncated by the next writer. A damaged record followed by more of the log is reported as
+//! corruption instead, leaving the file untouched.
+//!
+//! Content is streamed into place rather than buffered. The record's length is written last,
+//! claiming until then to run past the end of the file, so a write cut short always reads as
+//! torn.
+
+use crate::error::AppError;
+use crate::id::{IdRules, ItemId};
+use crate::memory::ensure_placeable;
+use crate::metadata::{
+    ContentDigest, DigestWriter, ItemMetadata, TrashEntry, is_valid_tag, unix_now, validate_tags,
+};
+use crate::storage::{
+    DEFAULT_HISTORY_LIMIT, DEFAULT_LOCK_TIMEOUT, LockGuard, LockMode, Staged, Storage, acquire_lock,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the log file `--backend logfile` keeps in the storage root.
+pub const LOG_FILE_NAME: &str = "store.log";
+
+/// First bytes of every store log.
+const MAGIC: &[u8; 8] = b"RSCTLOG\0";
+
+/// Layout of the records this build reads and writes; newer logs are refused.
+const FORMAT_VERSION: u32 = 1;
+
+/// Magic, format version and generation.
+const HEADER_LEN: u64 = 20;
+
+/// Payload length and checksum in front of every record.
+const FRAME_HEADER_LEN: u64 = 12;
+
+/// Buffer size used when replaying the log and streaming content into it.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Storage backend appending every change to a single portable log file.
+///
+/// It follows the same rules as [`crate::FilesystemStorage`]. Processes sharing the file are
+/// coordinated with an advisory lock on `<file>.lock`, held shared by reads and exclusively by
+/// writes; each operation first replays whatever other processes appended since. Operations on
+/// one value are serialized by an internal lock, so a shared reference can be used from several
+/// threads.
+#[derive(Debug)]
+pub struct LogFileStorage {
+    path: PathBuf,
+    state: Mutex<State>,
+    lock_timeout: Duration,
+    history_limit: usize,
+    id_rules: IdRules,
+}
+
+/// Sizes of the log before and after [`LogFileStorage::compact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CompactReport {
+    /// Bytes the log took before compaction.
+    pub before: u64,
+    /// Bytes the log takes now.
+    pub after: u64,
+}
+
+/// One change to the store, as written to the log.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Record {
+    /// Make `metadata` and the content following the header the current revision of `id`,
+    /// archiving the previous one while keeping at most `history` prior revisions.
+    Put { id: String, metadata: ItemMetadata, history: usize },
+    /// Replace the metadata of the current revision, such as its tags.
+    Meta { id: String, metadata: ItemMetadata },
+    /// Move an item to the trash; `deleted_at` is in nanoseconds since the Unix epoch.
+    Delete { id: String, deleted_at: u64 },
+    /// Remove an item without going through the trash.
+    Purge { id: String },
+    /// Bring the most recently trashed copy of an item back.
+    Restore { id: String },
+    /// Drop trashed items deleted at or before `cutoff` seconds, or all of them.
+    EmptyTrash { cutoff: Option<u64> },
+}
+
+/// In-memory index of the log, up to `end`.
+#[derive(Debug, Default)]
+struct State {
+    generation: u64,
+    /// Offset just past the last record applied, where the next one is appended.
+    end: u64,
+    items: BTreeMap<ItemId, Entry>,
+    /// Deleted items with their deletion time in nanoseconds, oldest deletion first.
+    trash: Vec<(u64, ItemId, Entry)>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    current: Revision,
+    /// Archived revisions by version number.
+    versions: BTreeMap<u64, Revision>,
+}
+
+#[derive(Debug, Clone)]
+struct Revision {
+    metadata: ItemMetadata,
+    content: Extent,
+}
+
+/// Position of a revision's content in the log.
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    offset: u64,
+    len: u64,
+}
+
+/// Outcome of reading the record at the end of the replayed log.
+enum Frame {
+    Record {
+        record: Record,
+        content: Extent,
+        next: u64,
+    },
+    /// The log ends cleanly here.
+    End,
+    /// The last record of the log is cut short or fails its checksum, as a torn write leaves.
+    Torn,
+    /// The record here is damaged although more of the log follows it.
+    Corrupt(&'static str),
+}
+
+/// The log file opened under its lock, with the index caught up to it.
+struct Session<'a> {
+    state: MutexGuard<'a, State>,
+    file: File,
+    _lock: LockGuard,
+}
+
+impl LogFileStorage {
+    /// Open the log at `path`, creating it and its parent directories if needed.
+    ///
+    /// A torn record at the end of the log is skipped; only the next write cuts it off.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, AppError> {
+        let storage = Self {
+            path: path.into(),
+            state: Mutex::default(),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            id_rules: IdRules::default(),
+        };
+        if let Some(parent) = storage.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mode = match fs::metadata(&storage.path) {
+            Ok(metadata) if metadata.len() >= HEADER_LEN => LockMode::Shared,
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => LockMode::Exclusive,
+        };
+        storage.session(mode)?;
+        Ok(storage)
+    }
+
+    /// Set how long operations wait for the lock held by other processes.
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Set how many prior revisions are kept per item; `0` disables history.
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set the rules identifiers given to this store's commands are checked against.
+    pub fn with_id_rules(mut self, rules: IdRules) -> Self {
+        self.id_rules = rules;
+        self
+    }
+
+    /// Location of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rewrite the log with only the records needed for the current items, their retained
+    /// revisions and the trash, replacing the file atomically.
+    pub fn compact(&self) -> Result<CompactReport, AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        let before = session.state.end;
+        // Removed again if compaction fails before the rename.
+        let scratch = Staged::at(self.sibling(".compact"));
+
+        let mut output = File::create(&scratch.path)?;
+        output.write_all(&header(session.state.generation + 1))?;
+        let mut end = HEADER_LEN;
+        let state = &session.state;
+        let trashed =
+            state.trash.iter().map(|(deleted_at, id, entry)| (id, entry, Some(deleted_at)));
+        let live = state.items.iter().map(|(id, entry)| (id, entry, None));
+        for (id, entry, deleted_at) in trashed.chain(live) {
+            for revision in entry.versions.values().chain([&entry.current]) {
+                let mut content = extent_reader(&session.file, revision.content)?;
+                let record = |_| Record::Put {
+                    id: id.to_string(),
+                    metadata: revision.metadata.clone(),
+                    history: usize::MAX,
+                };
+                let (_, copied, next) = write_record(&output, end, &mut content, record)?;
+                if copied.len < revision.content.len {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                end = next;
+            }
+            if let Some(&deleted_at) = deleted_at {
+                let record = |_| Record::Delete { id: id.to_string(), deleted_at };
+                end = write_record(&output, end, &mut io::empty(), record)?.2;
+            }
+        }
+        output.sync_all()?;
+        scratch.commit(&self.path)?;
+
+        *session.state = State::default();
+        session.file = File::open(&self.path)?;
+        refresh(&self.path, &mut session.state, &mut session.file, false)?;
+        Ok(CompactReport { before, after: session.state.end })
+    }
+
+    /// Append a new revision of `id` read from `reader`, replacing its tags with `tags` if given.
+    fn replace(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: Option<&[String]>,
+    ) -> Result<(), AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        let mut metadata = session.state.entry(id)?.current.metadata.clone();
+        metadata.touch_digest(placeholder_digest());
+        if let Some(content_type) = content_type {
+            metadata.content_type = Some(content_type.to_string());
+        }
+        if let Some(tags) = tags {
+            metadata.tags = tags.iter().cloned().collect();
+        }
+        session.append(&self.path, reader, self.put(id, metadata))
+    }
+
+    /// `path` with `suffix` appended to its file name.
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().map(OsString::from).unwrap_or_default();
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+
+    /// Lock the log, open it and replay the records appended since the last operation.
+    ///
+    /// Writers create the log if it is missing and truncate a torn record at its end.
+    fn session(&self, mode: LockMode) -> Result<Session<'_>, AppError> {
+        // A panic while holding the lock leaves the index behind the log, never ahead of it.
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let lock = acquire_lock(&self.sibling(".lock"), mode, self.lock_timeout, "the store log")?;
+        let writable = mode == LockMode::Exclusive;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(writable)
+            .create(writable)
+            .truncate(false)
+            .open(&self.path)?;
+        if writable && file.metadata()?.len() < HEADER_LEN {
+            initialize(&self.path, &mut file)?;
+        }
+        refresh(&self.path, &mut state, &mut file, writable)?;
+        Ok(Session { state, file, _lock: lock })
+    }
+
+    /// Build the record making `metadata`, with the digest of the content written after it, the
+    /// current revision of `id`.
+    fn put<'a>(
+        &self,
+        id: &'a ItemId,
+        metadata: ItemMetadata,
+    ) -> impl Fn(ContentDigest) -> Record + 'a {
+        let history = self.history_limit;
+        move |digest| Record::Put {
+            id: id.to_string(),
+            metadata: ItemMetadata { size: digest.size, sha256: digest.sha256, ..metadata.clone() },
+            history,
+        }
+    }
+
+    fn change_tags(
+        &self,
+        id: &ItemId,
+        tag: &str,
+        change: impl FnOnce(&mut ItemMetadata) -> bool,
+    ) -> Result<bool, AppError> {
+        if !is_valid_tag(tag) {
+            return Err(AppError::config_error(format!("invalid tag: {tag}")));
+        }
+        let mut session = self.session(LockMode::Exclusive)?;
+        let mut metadata = session.state.entry(id)?.current.metadata.clone();
+        if !change(&mut metadata) {
+            return Ok(false);
+        }
+        let record = |_| Record::Meta { id: id.to_string(), metadata: metadata.clone() };
+        session.append(&self.path, &mut io::empty(), record)?;
+        Ok(true)
+    }
+}
+
+impl Session<'_> {
+    /// Write the record built by `record` at the end of the log, followed by the content read
+    /// from `content`, sync it to disk and apply it to the index.
+    fn append(
+        &mut self,
+        path: &Path,
+        content: &mut dyn Read,
+        record: impl Fn(ContentDigest) -> Record,
+    ) -> Result<(), AppError> {
+        let offset = self.state.end;
+        let (record, extent, next) = write_record(&self.file, offset, content, record)?;
+        self.file.sync_data()?;
+        self.state.apply(record, extent).map_err(|message| inconsistent(path, offset, message))?;
+        self.state.end = next;
+        Ok(())
+    }
+}
+
+impl State {
+    fn entry(&self, id: &ItemId) -> Result<&Entry, AppError> {
+        self.items.get(id).ok_or_else(|| AppError::ItemNotFound(id.to_string()))
+    }
+
+    /// Apply one record to the index; `content` locates the content following a `Put`.
+    fn apply(&mut self, record: Record, content: Extent) -> Result<(), String> {
+        match record {
+            Record::Put { id, metadata, history } => {
+                let id = stored_id(&id)?;
+                let revision = Revision { metadata, content };
+                match self.items.get_mut(&id) {
+                    Some(entry) => {
+                        let previous = std::mem::replace(&mut entry.current, revision);
+                        if history > 0 {
+                            entry.versions.insert(previous.metadata.version, previous);
+                        }
+                        while entry.versions.len() > history {
+                            entry.versions.pop_first();
+                        }
+                    }
+                    None => {
+                        let entry = Entry { current: revision, versions: BTreeMap::new() };
+                        self.items.insert(id, entry);
+                    }
+                }
+            }
+            Record::Meta { id, metadata } => {
+                let entry = self.items.get_mut(&stored_id(&id)?).ok_or_else(|| missing(&id))?;
+                entry.current.metadata = metadata;
+            }
+            Record::Delete { id, deleted_at } => {
+                let id = stored_id(&id)?;
+                let entry = self.items.remove(&id).ok_or_else(|| missing(&id))?;
+                self.trash.push((deleted_at, id, entry));
+            }
+            Record::Purge { id } => {
+                self.items.remove(&stored_id(&id)?).ok_or_else(|| missing(&id))?;
+            }
+            Record::Restore { id } => {
+                let id = stored_id(&id)?;
+                let position = self
+                    .trash
+                    .iter()
+                    .rposition(|(_, trashed, _)| *trashed == id)
+                    .ok_or_else(|| format!("'{id}' is not in the trash"))?;
+                if self.items.contains_key(&id) {
+                    return Err(format!("'{id}' is restored over a live item"));
+                }
+                let (_, id, entry) = self.trash.remove(position);
+                self.items.insert(id, entry);
+            }
+            Record::EmptyTrash { cutoff } => {
+                self.trash.retain(|(deleted_at, ..)| {
+                    cutoff.is_some_and(|cutoff| deleted_at / 1_000_000_000 > cutoff)
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn stored_id(raw: &str) -> Result<ItemId, String> {
+    ItemId::from_stored(raw).ok_or_else(|| format!("invalid item identifier '{raw}'"))
+}
+
+fn missing(id: &str) -> String {
+    format!("'{id}' is not a live item")
+}
+
+fn inconsistent(path: &Path, offset: u64, message: String) -> AppError {
+    let message =
+        format!("store log {} is inconsistent at byte {offset}: {message}", path.display());
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+fn header(generation: u64) -> Vec<u8> {
+    [&MAGIC[..], &FORMAT_VERSION.to_le_bytes(), &generation.to_le_bytes()].concat()
+}
+
+/// Write the header of an empty log, unless the file holds something other than the start of
+/// one, as a crash while creating it can leave.
+fn initialize(path: &Path, file: &mut File) -> Result<(), AppError> {
+    let mut existing = Vec::new();
+    file.read_to_end(&mut existing)?;
+    if !header(0).starts_with(&existing) {
+        return Err(not_a_log(path));
+    }
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header(0))?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn not_a_log(path: &Path) -> AppError {
+    AppError::config_error(format!("{} is not a store log", path.display()))
+}
+
+/// Check the log header and return its generation.
+fn read_header(path: &Path, file: &mut File) -> Result<u64, AppError> {
+    let mut bytes = [0u8; HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0))?;
+    match file.read_exact(&mut bytes) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Err(not_a_log(path)),
+        result => result?,
+    }
+    if bytes[..8] != MAGIC[..] {
+        return Err(not_a_log(path));
+    }
+    let version = u32::from_le_bytes(bytes[8..12].try_into().expect("four bytes"));
+    if version > FORMAT_VERSION {
+        return Err(AppError::config_error(format!(
+            "store log {} uses format version {version}, newer than this build supports \
+             ({FORMAT_VERSION})",
+            path.display()
+        )));
+    }
+    Ok(u64::from_le_bytes(bytes[12..].try_into().expect("eight bytes")))
+}
+
+/// Bring the index up to the end of the log, starting over if it was compacted or shortened
+/// since. A torn record ends the replay and is cut off when `truncate` is set.
+fn refresh(
+    path: &Path,
+    state: &mut State,
+    file: &mut File,
+    truncate: bool,
+) -> Result<(), AppError> {
+    let generation = read_header(path, file)?;
+    let len = file.metadata()?.len();
+    if generation != state.generation || state.end < HEADER_LEN || len < state.end {
+        *state = State { generation, end: HEADER_LEN, ..State::default() };
+    }
+
+    let mut reader = BufReader::with_capacity(BUFFER_SIZE, &*file);
+    reader.seek(SeekFrom::Start(state.end))?;
+    loop {
+        match read_frame(&mut reader, state.end, len)? {
+            Frame::Record { record, content, next } => {
+                state
+                    .apply(record, content)
+                    .map_err(|message| inconsistent(path, state.end, message))?;
+                state.end = next;
+            }
+            Frame::End => return Ok(()),
+            Frame::Torn => break,
+            Frame::Corrupt(reason) => {
+                return Err(inconsistent(path, state.end, reason.to_string()));
+            }
+        }
+    }
+    if truncate {
+        file.set_len(state.end)?;
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Read the record at `offset`, verifying its checksum while streaming past its content.
+///
+/// A damaged record only counts as torn when it reaches the end of the log.
+fn read_frame(reader: &mut impl Read, offset: u64, len: u64) -> Result<Frame, AppError> {
+    let remaining = len - offset;
+    if remaining == 0 {
+        return Ok(Frame::End);
+    }
+    let mut prefix = [0u8; FRAME_HEADER_LEN as usize + 4];
+    if remaining < prefix.len() as u64 {
+        return Ok(Frame::Torn);
+    }
+    reader.read_exact(&mut prefix)?;
+    let payload_len = u64::from_le_bytes(prefix[..8].try_into().expect("eight bytes"));
+    let checksum = u32::from_le_bytes(prefix[8..12].try_into().expect("four bytes"));
+    let header_len = u32::from_le_bytes(prefix[12..].try_into().expect("four bytes")) as u64;
+    if payload_len > remaining - FRAME_HEADER_LEN {
+        return Ok(Frame::Torn);
+    }
+    let next = offset + FRAME_HEADER_LEN + payload_len;
+    let damaged = |reason| Ok(if next == len { Frame::Torn } else { Frame::Corrupt(reason) });
+    if payload_len < 4 + header_len {
+        return damaged("record header runs past its payload");
+    }
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&prefix[12..]);
+    let mut header = vec![0u8; header_len as usize];
+    reader.read_exact(&mut header)?;
+    hasher.update(&header);
+    let content_len = payload_len - 4 - header_len;
+    let mut sink = HashWriter { hasher: &mut hasher, inner: io::sink() };
+    io::copy(&mut reader.take(content_len), &mut sink)?;
+    if hasher.finalize() != checksum {
+        return damaged("record fails its checksum");
+    }
+    let Ok(record) = serde_json::from_slice(&header) else {
+        return damaged("record header is not valid JSON");
+    };
+
+    Ok(Frame::Record {
+        record,
+        content: Extent { offset: next - content_len, len: content_len },
+        next,
+    })
+}
+
+/// Digest whose JSON is at least as long as that of any content, reserving room for the real one.
+fn placeholder_digest() -> ContentDigest {
+    ContentDigest { size: u64::MAX, sha256: "0".repeat(64) }
+}
+
+/// Write the record built by `record` at `offset` followed by the content read from `content`,
+/// returning the record, where its content landed and the offset just past it.
+///
+/// `record` is called with a placeholder digest to size the header, then with the digest of the
+/// content once it has been streamed; the header is padded with spaces to the reserved size.
+fn write_record(
+    file: &File,
+    offset: u64,
+    content: &mut dyn Read,
+    record: impl Fn(ContentDigest) -> Record,
+) -> Result<(Record, Extent, u64), AppError> {
+    let reserved = serde_json::to_vec(&record(placeholder_digest())).map_err(io::Error::from)?;
+    let header_len = reserved.len();
+    let mut file = file;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut writer = BufWriter::with_capacity(BUFFER_SIZE, file);
+    // Until patched below, the length claims the record runs past the end of the file.
+    writer.write_all(&u64::MAX.to_le_bytes())?;
+    writer.write_all(&[0; 8])?;
+    writer.write_all(&vec![b' '; header_len])?;
+
+    let mut content_checksum = crc32fast::Hasher::new();
+    let mut writer = DigestWriter::new(HashWriter { hasher: &mut content_checksum, inner: writer });
+    io::copy(content, &mut writer)?;
+    let (writer, digest) = writer.finish()?;
+    let mut file = writer.inner.into_inner().map_err(io::IntoInnerError::into_error)?;
+
+    let content_len = digest.size;
+    let record = record(digest);
+    let mut header = serde_json::to_vec(&record).map_err(io::Error::from)?;
+    if header.len() > header_len {
+        return Err(io::Error::other("record header outgrew the space reserved for it").into());
+    }
+    header.resize(header_len, b' ');
+    let payload_prefix = [&(header_len as u32).to_le_bytes()[..], &header].concat();
+    let mut checksum = crc32fast::Hasher::new();
+    checksum.update(&payload_prefix);
+    checksum.combine(&content_checksum);
+    let payload_len = payload_prefix.len() as u64 + content_len;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(&payload_len.to_le_bytes())?;
+    file.write_all(&checksum.finalize().to_le_bytes())?;
+    file.write_all(&payload_prefix)?;
+
+    let next = offset + FRAME_HEADER_LEN + payload_len;
+    Ok((record, Extent { offset: next - content_len, len: content_len }, next))
+}
+
+/// Reader over the content at `extent`.
+fn extent_reader(file: &File, extent: Extent) -> io::Result<io::Take<&File>> {
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(extent.offset))?;
+    Ok(reader.take(extent.len))
+}
+
+/// Copy the content at `extent` into `writer`, returning the number of bytes written.
+fn read_extent(file: &File, extent: Extent, writer: &mut dyn Write) -> Result<u64, AppError> {
+    let copied = io::copy(&mut extent_reader(file, extent)?, writer)?;
+    if copied < extent.len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(copied)
+}
+
+/// Writer feeding everything written to a checksum on its way to `inner`.
+struct HashWriter<'a, W> {
+    hasher: &'a mut crc32fast::Hasher,
+    inner: W,
+}
+
+impl<W: Write> Write for HashWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn trash_entry(deleted_at: u64, id: &ItemId, entry: &Entry) -> TrashEntry {
+    TrashEntry {
+        id: id.to_string(),
+        deleted_at: deleted_at / 1_000_000_000,
+        size: entry.current.content.len,
+    }
+}
+
+impl Storage for LogFileStorage {
+    fn id_rules(&self) -> IdRules {
+        self.id_rules
+    }
+
+    fn add_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.add_item_from_reader(id, &mut &content[..], content_type)
+    }
+
+    fn update_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.update_item_from_reader(id, &mut &content[..], content_type)
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        let current = session.state.entry(id)?.current.clone();
+        let mut metadata = current.metadata;
+        metadata.touch_digest(placeholder_digest());
+        // A handle of its own, so copying the current content does not move the write position.
+        let source = File::open(&self.path)?;
+        let mut combined = extent_reader(&source, current.content)?.chain(content);
+        session.append(&self.path, &mut combined, self.put(id, metadata))
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        let mut content = Vec::new();
+        self.read_item_into(id, &mut content)?;
+        Ok(content)
+    }
+
+    fn add_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.add_tagged_item_from_reader(id, reader, content_type, &[])
+    }
+
+    fn update_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.replace(id, reader, content_type, None)
+    }
+
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let mut session = self.session(LockMode::Exclusive)?;
+        ensure_placeable(&session.state.items, id)?;
+        let mut metadata = ItemMetadata::from_digest(placeholder_digest(), content_type);
+        metadata.tags.extend(tags.iter().cloned());
+        session.append(&self.path, reader, self.put(id, metadata))
+    }
+
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        self.replace(id, reader, content_type, Some(tags))
+    }
+
+    fn read_item_into(&self, id: &ItemId, writer: &mut dyn Write) -> Result<u64, AppError> {
+        let session = self.session(LockMode::Shared)?;
+        read_extent(&session.file, session.state.entry(id)?.current.content, writer)
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        let session = self.session(LockMode::Shared)?;
+        Ok(session.state.entry(id)?.current.metadata.clone())
+    }
+
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |metadata| metadata.tags.insert(tag.to_string()))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |metadata| metadata.tags.remove(tag))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        let session = self.session(LockMode::Shared)?;
+        let entry = session.state.entry(id)?;
+        Ok(entry
+            .versions
+            .values()
+            .chain([&entry.current])
+            .map(|revision| revision.metadata.clone())
+            .collect())
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        let session = self.session(LockMode::Shared)?;
+        let entry = session.state.entry(id)?;
+        let revision = if version == entry.current.metadata.version {
+            &entry.current
+        } else {
+            entry
+                .versions
+                .get(&version)
+                .ok_or_else(|| AppError::VersionNotFound(id.to_string(), version))?
+        };
+        read_extent(&session.file, revision.content, writer)
+    }
+
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        Ok(self.session(LockMode::Shared)?.state.items.keys().cloned().collect())
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        session.state.entry(id)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        // Keep deletions ordered even when the clock does not advance between them.
+        let deleted_at = session.state.trash.last().map_or(now, |(last, ..)| now.max(last + 1));
+        let record = |_| Record::Delete { id: id.to_string(), deleted_at };
+        session.append(&self.path, &mut io::empty(), record)
+    }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        session.state.entry(id)?;
+        session.append(&self.path, &mut io::empty(), |_| Record::Purge { id: id.to_string() })
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        let session = self.session(LockMode::Shared)?;
+        let trash = &session.state.trash;
+        Ok(trash
+            .iter()
+            .map(|(deleted_at, id, entry)| trash_entry(*deleted_at, id, entry))
+            .collect())
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut session = self.session(LockMode::Exclusive)?;
+        ensure_placeable(&session.state.items, id)?;
+        if !session.state.trash.iter().any(|(_, trashed, _)| trashed == id) {
+            return Err(AppError::ItemNotFound(id.to_string()));
+        }
+        session.append(&self.path, &mut io::empty(), |_| Record::Restore { id: id.to_string() })
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        let cutoff = older_than.map(|age| unix_now().saturating_sub(age.as_secs()));
+        let mut session = self.session(LockMode::Exclusive)?;
+        let removed: Vec<TrashEntry> = session
+            .state
+            .trash
+            .iter()
+            .map(|(deleted_at, id, entry)| trash_entry(*deleted_at, id, entry))
+            .filter(|entry| cutoff.is_none_or(|cutoff| entry.deleted_at <= cutoff))
+            .collect();
+        if !removed.is_empty() {
+            session.append(&self.path, &mut io::empty(), |_| Record::EmptyTrash { cutoff })?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::id;
+    use tempfile::TempDir;
+
+    fn open() -> (TempDir, LogFileStorage) {
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let storage = LogFileStorage::open(dir.path().join(LOG_FILE_NAME)).unwrap();
+        (dir, storage)
+    }
+
+    #[test]
+    fn reopening_rebuilds_the_index_from_the_log() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("team/note"), b"one", Some("text/plain")).unwrap();
+        storage.update_item(&id("team/note"), b"two", None).unwrap();
+        storage.add_tag(&id("team/note"), "work").unwrap();
+        storage.add_item(&id("gone"), b"x", None).unwrap();
+        storage.delete_item(&id("gone")).unwrap();
+
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        assert_eq!(reopened.get_item(&id("team/note")).unwrap(), b"two");
+        let versions = reopened.list_versions(&id("team/note")).unwrap();
+        assert_eq!(versions.iter().map(|metadata| metadata.version).collect::<Vec<_>>(), [1, 2]);
+        assert!(versions[1].tags.contains("work"));
+        assert_eq!(reopened.list_trash().unwrap().len(), 1);
+        assert_eq!(reopened.list_items().unwrap(), [id("team/note")]);
+    }
+
+    #[test]
+    fn changes_by_another_handle_are_seen_on_the_next_operation() {
+        let (_dir, first) = open();
+        let second = LogFileStorage::open(first.path()).unwrap();
+
+        first.add_item(&id("note"), b"from first", None).unwrap();
+        assert_eq!(second.get_item(&id("note")).unwrap(), b"from first");
+        second.append_item(&id("note"), b", then second").unwrap();
+        first.compact().unwrap();
+        assert_eq!(second.get_item(&id("note")).unwrap(), b"from first, then second");
+        second.delete_item(&id("note")).unwrap();
+        assert!(first.list_items().unwrap().is_empty());
+    }
+
+    #[test]
+    fn torn_tail_is_skipped_on_open_and_truncated_by_the_next_write() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("kept"), b"complete", None).unwrap();
+        storage.add_item(&id("torn"), b"cut short by a crash", None).unwrap();
+        let full = fs::metadata(storage.path()).unwrap().len();
+        OpenOptions::new().write(true).open(storage.path()).unwrap().set_len(full - 5).unwrap();
+
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        assert_eq!(reopened.list_items().unwrap(), [id("kept")]);
+        assert_eq!(fs::metadata(storage.path()).unwrap().len(), full - 5);
+        reopened.add_item(&id("next"), b"appended after the repair", None).unwrap();
+        assert_eq!(LogFileStorage::open(storage.path()).unwrap().list_items().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn records_failing_their_checksum_end_the_log() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("kept"), b"complete", None).unwrap();
+        storage.add_item(&id("corrupt"), b"flipped", None).unwrap();
+        let mut bytes = fs::read(storage.path()).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        fs::write(storage.path(), bytes).unwrap();
+
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        assert_eq!(reopened.list_items().unwrap(), [id("kept")]);
+    }
+
+    #[test]
+    fn stream_failing_midway_leaves_a_torn_record_the_next_write_replaces() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("kept"), b"complete", None).unwrap();
+        let mut failing = (&[7u8; 100_000][..]).chain(FailingReader);
+        assert!(storage.add_item_from_reader(&id("partial"), &mut failing, None).is_err());
+
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        assert_eq!(reopened.list_items().unwrap(), [id("kept")]);
+        storage.append_item(&id("kept"), b" and appended").unwrap();
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        assert_eq!(reopened.get_item(&id("kept")).unwrap(), b"complete and appended");
+        assert_eq!(reopened.item_metadata(&id("kept")).unwrap().size, 21);
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("source went away"))
+        }
+    }
+
+    #[test]
+    fn damaged_record_inside_the_log_is_reported_without_touching_the_file() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("first"), b"flipped", None).unwrap();
+        storage.add_item(&id("second"), b"intact", None).unwrap();
+        storage.add_item(&id("third"), b"intact", None).unwrap();
+        let mut bytes = fs::read(storage.path()).unwrap();
+        let position = bytes.windows(7).position(|window| window == b"flipped").unwrap();
+        bytes[position] ^= 0xff;
+        fs::write(storage.path(), &bytes).unwrap();
+
+        let result = LogFileStorage::open(storage.path());
+        assert!(
+            matches!(result, Err(AppError::Io(ref err)) if err.kind() == io::ErrorKind::InvalidData)
+        );
+        assert_eq!(fs::read(storage.path()).unwrap(), bytes);
+    }
+
+    #[test]
+    fn compact_drops_superseded_records_and_keeps_the_store() {
+        let (_dir, storage) = open();
+        let storage = storage.with_history_limit(1);
+        storage.add_item(&id("note"), b"v1", None).unwrap();
+        for content in [b"v2", b"v3", b"v4"] {
+            storage.update_item(&id("note"), content, None).unwrap();
+        }
+        storage.add_item(&id("trashed"), b"bin", None).unwrap();
+        storage.delete_item(&id("trashed")).unwrap();
+        storage.add_item(&id("purged"), &[0; 4096], None).unwrap();
+        storage.purge_item(&id("purged")).unwrap();
+
+        let report = storage.compact().unwrap();
+        assert!(report.after < report.before, "{report:?}");
+        assert_eq!(report.after, fs::metadata(storage.path()).unwrap().len());
+
+        let reopened = LogFileStorage::open(storage.path()).unwrap();
+        let versions = reopened.list_versions(&id("note")).unwrap();
+        assert_eq!(versions.iter().map(|metadata| metadata.version).collect::<Vec<_>>(), [3, 4]);
+        let mut previous = Vec::new();
+        reopened.read_version_into(&id("note"), 3, &mut previous).unwrap();
+        assert_eq!(previous, b"v3");
+        reopened.restore_item(&id("trashed")).unwrap();
+        assert_eq!(reopened.get_item(&id("trashed")).unwrap(), b"bin");
+        let grown = fs::metadata(storage.path()).unwrap().len();
+        assert_eq!(storage.compact().unwrap().before, grown);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn failed_compaction_removes_its_scratch_file_and_keeps_the_log() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("note"), b"kept", None).unwrap();
+        let before = fs::read(storage.path()).unwrap();
+        // Every write to the scratch file then fails as on a full disk.
+        let scratch = storage.sibling(".compact");
+        std::os::unix::fs::symlink("/dev/full", &scratch).unwrap();
+
+        assert!(matches!(storage.compact(), Err(AppError::Io(_))));
+        assert!(fs::symlink_metadata(&scratch).is_err());
+        assert_eq!(fs::read(storage.path()).unwrap(), before);
+        assert_eq!(storage.get_item(&id("note")).unwrap(), b"kept");
+    }
+
+    #[test]
+    fn open_refuses_other_files_and_newer_formats() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+        fs::write(&path, b"not a log at all, just text").unwrap();
+        assert!(matches!(LogFileStorage::open(&path), Err(AppError::ConfigError(_))));
+
+        let mut bytes = header(0);
+        bytes[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+        let result = LogFileStorage::open(&path);
+        assert!(
+            matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("newer"))
+        );
+    }
+}
@@ -1,6 +1,15 @@
-use clap::{Parser, Subcommand};
-use rs_cli_tmpl::commands;
-use rs_cli_tmpl::error::AppError;
+use clap::{Args, Parser, Subcommand};
+use rs_cli_tmpl::cli::{
+    self, Backend, ColorMode, Config, ContentSource, LOG_FILE_NAME, OutputFormat, Settings,
+    StdoutReporter,
+};
+use rs_cli_tmpl::commands::{self, AddOptions, DeleteOptions, ListOptions, ShowOptions};
+use rs_cli_tmpl::{
+    AppError, Event, FilesystemStorage, LogFileStorage, Reporter, SearchMode, Storage,
+};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "rs-cli-tmpl")]
@@ -9,6 +18,18 @@ use rs_cli_tmpl::error::AppError;
     long_about = None
 )]
 struct Cli {
+    /// Output format for command results and errors [default: human]
+    #[arg(long, global = true, value_enum)]
+    format: Option<OutputFormat>,
+    /// Storage root directory (overrides RS_CLI_TMPL_HOME and the XDG defaults)
+    #[arg(long, global = true, value_name = "PATH")]
+    store: Option<PathBuf>,
+    /// Storage backend holding the items [default: filesystem]
+    #[arg(long, global = true, value_enum)]
+    backend: Option<Backend>,
+    /// When to use ANSI colors in human output [default: auto]
+    #[arg(long, global = true, value_enum)]
+    color: Option<ColorMode>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,32 +41,457 @@ enum Commands {
     Add {
         /// Identifier for the item
         id: String,
-        /// Content to persist with the item
+        #[command(flatten)]
+        content: ContentArgs,
+        /// Overwrite the item if it already exists, replacing its tags with the given ones
         #[clap(short, long)]
-        content: String,
+        force: bool,
+        /// Media type to record in the item's metadata (e.g. `text/plain`)
+        #[clap(long)]
+        content_type: Option<String>,
+        /// Tag to attach to the item (repeatable)
+        #[clap(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
     },
-    /// List all stored item identifiers
+    /// Replace the content of an existing item
+    Update {
+        /// Identifier for the item
+        id: String,
+        #[command(flatten)]
+        content: ContentArgs,
+        /// Media type to record in the item's metadata
+        #[clap(long)]
+        content_type: Option<String>,
+    },
+    /// Append content to the end of an existing item
+    Append {
+        /// Identifier for the item
+        id: String,
+        #[command(flatten)]
+        content: ContentArgs,
+    },
+    /// Open an existing item in $EDITOR and save the result
+    Edit {
+        /// Identifier for the item to edit
+        id: String,
+    },
+    /// Print the content of a stored item
+    #[clap(alias = "cat")]
+    Show {
+        /// Identifier for the item to print
+        id: String,
+        /// Write binary content even when stdout is a terminal
+        #[clap(long)]
+        raw: bool,
+        /// Print a retained revision instead of the current content (see `history`)
+        #[clap(long, value_name = "N")]
+        version: Option<u64>,
+    },
+    /// List the retained revisions of an item
+    History {
+        /// Identifier for the item
+        id: String,
+    },
+    /// Restore a retained revision of an item as its newest revision
+    Rollback {
+        /// Identifier for the item
+        id: String,
+        /// Revision number to restore (see `history`)
+        version: u64,
+    },
+    /// List stored item identifiers, or those inside a namespace
     #[clap(alias = "ls")]
-    List,
-    /// Delete an item from storage
+    List {
+        /// Namespace to list, such as `team/service`
+        prefix: Option<String>,
+        /// List every item below the namespace instead of folding nested namespaces
+        #[clap(short, long)]
+        recursive: bool,
+        /// Show size, last update time and content type for each item
+        #[clap(short, long)]
+        long: bool,
+        /// Only list items carrying this tag; repeat to require several
+        #[clap(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// List items carrying any of the given tags rather than all of them
+        #[clap(long, requires = "tags")]
+        any_tag: bool,
+    },
+    /// Find items whose content contains a string or pattern
+    #[clap(alias = "grep")]
+    Search {
+        /// Text to look for
+        query: String,
+        /// Match letters regardless of case
+        #[clap(short, long)]
+        ignore_case: bool,
+        /// Treat the query as a regular expression
+        #[clap(short = 'e', long)]
+        regex: bool,
+    },
+    /// Maintain the search index used by `search`
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+    /// Check the store for problems such as names differing only in letter case
+    Doctor,
+    /// Rewrite the store log to reclaim the space of superseded records (logfile backend)
+    Compact,
+    /// Attach or detach item tags
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Move an item to the trash
     #[clap(alias = "rm")]
     Delete {
         /// Identifier for the item to delete
         id: String,
+        /// Skip the confirmation prompt enabled by the `confirm` setting
+        #[clap(short, long)]
+        yes: bool,
+        /// Remove the item permanently instead of moving it to the trash
+        #[clap(long)]
+        purge: bool,
+        /// Delete every item below the namespace `id`; always asks unless `--yes` is given
+        #[clap(short, long)]
+        recursive: bool,
+    },
+    /// Bring a deleted item back from the trash
+    Restore {
+        /// Identifier for the item to restore
+        id: String,
+    },
+    /// Inspect or empty the trash of deleted items
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Inspect or change configuration settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
 }
 
+impl Commands {
+    /// Whether the command changes the store, so leftovers of interrupted writes are cleaned up
+    /// before it runs. Readers never see those leftovers and are spared the walk over the store.
+    fn writes(&self) -> bool {
+        match self {
+            Commands::Add { .. }
+            | Commands::Update { .. }
+            | Commands::Append { .. }
+            | Commands::Edit { .. }
+            | Commands::Rollback { .. }
+            | Commands::Tag { .. }
+            | Commands::Delete { .. }
+            | Commands::Restore { .. }
+            | Commands::Compact
+            | Commands::Index { action: IndexAction::Rebuild }
+            | Commands::Trash { action: TrashAction::Empty { .. } } => true,
+            Commands::Show { .. }
+            | Commands::History { .. }
+            | Commands::List { .. }
+            | Commands::Search { .. }
+            | Commands::Index { action: IndexAction::Verify }
+            | Commands::Doctor
+            | Commands::Trash { action: TrashAction::List }
+            | Commands::Config { .. } => false,
+        }
+    }
+}
+
+/// Content input shared by commands that write items.
+///
+/// Without `--content` or `--from-file`, content is read from stdin when it is piped and from
+/// an editor session otherwise.
+#[derive(Args)]
+struct ContentArgs {
+    /// Content to persist with the item (`-` reads from stdin)
+    #[clap(short, long, conflicts_with = "from_file")]
+    content: Option<String>,
+    /// Read the content from a file
+    #[clap(long, value_name = "PATH")]
+    from_file: Option<PathBuf>,
+}
+
+impl ContentArgs {
+    fn source(self) -> ContentSource {
+        ContentSource::resolve(self.content, self.from_file, io::stdin().is_terminal())
+    }
+
+    fn read(self, config: &Config) -> Result<Vec<u8>, AppError> {
+        self.source().read(&editor(config))
+    }
+
+    fn open(self, config: &Config) -> Result<Box<dyn io::Read>, AppError> {
+        self.source().open(&editor(config))
+    }
+}
+
+#[derive(Subcommand)]
+enum IndexAction {
+    /// Build the index from every item, creating it if the store has none
+    Rebuild,
+    /// Check that the index matches item content
+    Verify,
+}
+
+#[derive(Subcommand)]
+enum TagAction {
+    /// Attach a tag to an item
+    Add {
+        /// Identifier for the item
+        id: String,
+        /// Tag to attach (letters, digits, `-`, `_` or `.`)
+        tag: String,
+    },
+    /// Detach a tag from an item
+    #[clap(alias = "rm")]
+    Remove {
+        /// Identifier for the item
+        id: String,
+        /// Tag to detach
+        tag: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List deleted items that can still be restored
+    #[clap(alias = "ls")]
+    List,
+    /// Permanently remove items from the trash
+    Empty {
+        /// Only remove items deleted at least this long ago (e.g. `30days`, `12h`)
+        #[clap(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+        older_than: Option<Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective value of a setting
+    Get {
+        #[arg(help = setting_help())]
+        key: String,
+    },
+    /// Persist a setting in the config file
+    Set {
+        #[arg(help = setting_help())]
+        key: String,
+        /// New value for the setting
+        value: String,
+    },
+    /// List every setting with its effective value and source
+    List,
+    /// Print the location of the config file
+    Path,
+}
+
+/// Help for the setting name argument, listing every key so new settings show up by themselves.
+fn setting_help() -> String {
+    format!("Setting name ({})", cli::KEYS.join(", "))
+}
+
 fn main() {
     let cli = Cli::parse();
+    let flags = Settings {
+        format: cli.format,
+        store: cli.store,
+        color: cli.color,
+        backend: cli.backend,
+        ..Settings::default()
+    };
 
-    let result: Result<(), AppError> = match cli.command {
-        Commands::Add { id, content } => commands::add(&id, &content),
-        Commands::List => commands::list().map(|_| ()),
-        Commands::Delete { id } => commands::delete(&id),
+    let config = match Config::load(flags) {
+        Ok(config) => config,
+        Err(e) => {
+            StdoutReporter::new(cli.format.unwrap_or_default()).error(&e);
+            std::process::exit(1);
+        }
+    };
+    let color = |is_terminal: bool| match config.color() {
+        ColorMode::Auto => is_terminal,
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+    };
+    let reporter = StdoutReporter::new(config.format())
+        .with_color(color(io::stdout().is_terminal()))
+        .with_error_color(color(io::stderr().is_terminal()));
+
+    let result = match cli.command {
+        Commands::Config { action } => run_config(action, &config, &reporter),
+        command => config.store().and_then(|root| match config.backend() {
+            Backend::Filesystem => {
+                let storage = FilesystemStorage::new(root)
+                    .with_lock_timeout(config.lock_timeout())
+                    .with_history_limit(config.history_limit())
+                    .with_id_rules(config.id_rules());
+                if command.writes() {
+                    storage.recover()?;
+                }
+                match command {
+                    Commands::Index { action } => run_index(action, &storage, &reporter),
+                    command => run(command, &storage, &reporter, &config),
+                }
+            }
+            Backend::Sqlite => run_sqlite(command, root, &reporter, &config),
+            Backend::Logfile => {
+                let storage = LogFileStorage::open(root.join(LOG_FILE_NAME))?
+                    .with_lock_timeout(config.lock_timeout())
+                    .with_history_limit(config.history_limit())
+                    .with_id_rules(config.id_rules());
+                match command {
+                    Commands::Compact => commands::compact(&storage, &reporter).map(|_| ()),
+                    command => run(command, &storage, &reporter, &config),
+                }
+            }
+        }),
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
+        reporter.error(&e);
         std::process::exit(1);
     }
 }
+
+#[cfg(feature = "sqlite")]
+fn run_sqlite(
+    command: Commands,
+    root: PathBuf,
+    reporter: &StdoutReporter,
+    config: &Config,
+) -> Result<(), AppError> {
+    use rs_cli_tmpl::{SqliteStorage, cli::DATABASE_FILE_NAME};
+
+    let storage = SqliteStorage::open(root.join(DATABASE_FILE_NAME))?
+        .with_lock_timeout(config.lock_timeout())?
+        .with_history_limit(config.history_limit())
+        .with_id_rules(config.id_rules());
+    run(command, &storage, reporter, config)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn run_sqlite(
+    _command: Commands,
+    _root: PathBuf,
+    _reporter: &StdoutReporter,
+    _config: &Config,
+) -> Result<(), AppError> {
+    Err(AppError::ConfigError(
+        "the sqlite backend is not included in this build (rebuild with `--features sqlite`)"
+            .to_string(),
+    ))
+}
+
+fn run(
+    command: Commands,
+    storage: &impl Storage,
+    reporter: &StdoutReporter,
+    config: &Config,
+) -> Result<(), AppError> {
+    match command {
+        Commands::Add { id, content, force, content_type, tags } => {
+            let mut reader = content.open(config)?;
+            let options = AddOptions { force, content_type, tags };
+            commands::add_from_reader(storage, reporter, &id, &mut reader, &options)
+        }
+        Commands::Update { id, content, content_type } => {
+            let content = content.read(config)?;
+            commands::update(storage, reporter, &id, &content, content_type.as_deref())
+        }
+        Commands::Append { id, content } => {
+            let content = content.read(config)?;
+            commands::append(storage, reporter, &id, &content)
+        }
+        Commands::Edit { id } => commands::edit(storage, reporter, &id, &editor(config)),
+        Commands::Show { id, raw, version } => {
+            commands::show(storage, reporter, &id, &ShowOptions { raw, version }).map(|_| ())
+        }
+        Commands::History { id } => commands::history(storage, reporter, &id).map(|_| ()),
+        Commands::Rollback { id, version } => commands::rollback(storage, reporter, &id, version),
+        Commands::List { prefix, recursive, long, tags, any_tag } => {
+            let options = ListOptions { long, tags, any_tag, prefix, recursive };
+            commands::list(storage, reporter, &options).map(|_| ())
+        }
+        Commands::Search { query, ignore_case, regex } => {
+            let mode = SearchMode { ignore_case, regex };
+            commands::search(storage, reporter, &query, mode).map(|_| ())
+        }
+        Commands::Index { .. } => Err(AppError::ConfigError(
+            "the search index is only kept by the filesystem backend".to_string(),
+        )),
+        Commands::Doctor => commands::doctor(storage, reporter).map(|_| ()),
+        Commands::Compact => Err(AppError::ConfigError(
+            "only the logfile backend keeps a log to compact".to_string(),
+        )),
+        Commands::Tag { action } => match action {
+            TagAction::Add { id, tag } => commands::tag_add(storage, reporter, &id, &tag),
+            TagAction::Remove { id, tag } => commands::tag_remove(storage, reporter, &id, &tag),
+        },
+        Commands::Delete { id, yes, purge, recursive } => {
+            let prompt = match (recursive, purge) {
+                (true, true) => format!("Permanently delete every item under '{id}'?"),
+                (true, false) => format!("Delete every item under '{id}'?"),
+                (false, true) => format!("Permanently delete item '{id}'?"),
+                (false, false) => format!("Delete item '{id}'?"),
+            };
+            if (config.confirm() || recursive) && !yes && !confirm(&prompt)? {
+                reporter.report(&Event::DeleteAborted { id });
+                return Ok(());
+            }
+            commands::delete(storage, reporter, &id, &DeleteOptions { purge, recursive })
+        }
+        Commands::Restore { id } => commands::restore(storage, reporter, &id),
+        Commands::Trash { action } => match action {
+            TrashAction::List => commands::trash_list(storage, reporter).map(|_| ()),
+            TrashAction::Empty { older_than } => {
+                commands::trash_empty(storage, reporter, older_than).map(|_| ())
+            }
+        },
+        Commands::Config { action } => run_config(action, config, reporter),
+    }
+}
+
+fn run_index(
+    action: IndexAction,
+    storage: &FilesystemStorage,
+    reporter: &StdoutReporter,
+) -> Result<(), AppError> {
+    match action {
+        IndexAction::Rebuild => commands::index_rebuild(storage, reporter).map(|_| ()),
+        IndexAction::Verify => commands::index_verify(storage, reporter).map(|_| ()),
+    }
+}
+
+fn run_config(
+    action: ConfigAction,
+    config: &Config,
+    reporter: &StdoutReporter,
+) -> Result<(), AppError> {
+    match action {
+        ConfigAction::Get { key } => commands::config_get(config, reporter, &key).map(|_| ()),
+        ConfigAction::Set { key, value } => {
+            commands::config_set(reporter, &cli::config_path()?, &key, &value)
+        }
+        ConfigAction::List => commands::config_list(config, reporter).map(|_| ()),
+        ConfigAction::Path => commands::config_path(reporter).map(|_| ()),
+    }
+}
+
+fn editor(config: &Config) -> String {
+    config.editor().unwrap_or_else(|| cli::DEFAULT_EDITOR.to_string())
+}
+
+/// Ask a yes/no question on stderr and read the answer from stdin.
+fn confirm(prompt: &str) -> Result<bool, AppError> {
+    eprint!("{prompt} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
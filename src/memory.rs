@@ -0,0 +1,390 @@
+//! Storage backend keeping every item in memory.
+
+use crate::error::AppError;
+use crate::id::{IdRules, ItemId};
+use crate::metadata::{
+    ContentDigest, ItemMetadata, TrashEntry, is_valid_tag, unix_now, validate_tags,
+};
+use crate::storage::{DEFAULT_HISTORY_LIMIT, Storage};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Storage backend holding items in memory, for tests and ephemeral sessions.
+///
+/// It follows the same rules as [`crate::FilesystemStorage`]: identifiers are checked against
+/// [`Storage::id_rules`] by the commands, a name is either an item or a namespace, names
+/// differing only in letter case are refused, and deleted items go to a trash they can be
+/// restored from. Nothing outlives the value. Operations are serialized by an internal lock,
+/// so a shared reference can be used from several threads.
+#[derive(Debug)]
+pub struct MemoryStorage {
+    state: Mutex<State>,
+    history_limit: usize,
+    id_rules: IdRules,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    items: BTreeMap<ItemId, Entry>,
+    /// Deleted items with their deletion time in nanoseconds, oldest deletion first.
+    trash: Vec<(u128, ItemId, Entry)>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    content: Vec<u8>,
+    metadata: ItemMetadata,
+    /// Archived revisions by version number.
+    versions: BTreeMap<u64, (Vec<u8>, ItemMetadata)>,
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStorage {
+    /// Create an empty storage.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::default(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            id_rules: IdRules::default(),
+        }
+    }
+
+    /// Set how many prior revisions are kept per item; `0` disables history.
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set the rules identifiers given to this store's commands are checked against.
+    pub fn with_id_rules(mut self, rules: IdRules) -> Self {
+        self.id_rules = rules;
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while holding the lock cannot leave an entry half written.
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert(
+        &self,
+        id: &ItemId,
+        content: Vec<u8>,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        let mut state = self.state();
+        ensure_placeable(&state.items, id)?;
+        let mut metadata = ItemMetadata::from_digest(ContentDigest::of(&content), content_type);
+        metadata.tags.extend(tags.iter().cloned());
+        state.items.insert(id.clone(), Entry { content, metadata, versions: BTreeMap::new() });
+        Ok(())
+    }
+
+    /// Replace an item's content, archiving the current revision first, and its tags with `tags`
+    /// if given.
+    fn replace(
+        &self,
+        id: &ItemId,
+        change: impl FnOnce(&[u8]) -> Vec<u8>,
+        content_type: Option<&str>,
+        tags: Option<&[String]>,
+    ) -> Result<(), AppError> {
+        let mut state = self.state();
+        let entry = state.entry_mut(id)?;
+        let content = change(&entry.content);
+
+        if self.history_limit > 0 {
+            let current = (entry.content.clone(), entry.metadata.clone());
+            entry.versions.insert(entry.metadata.version, current);
+        }
+        while entry.versions.len() > self.history_limit {
+            entry.versions.pop_first();
+        }
+        entry.metadata.touch_digest(ContentDigest::of(&content));
+        if let Some(content_type) = content_type {
+            entry.metadata.content_type = Some(content_type.to_string());
+        }
+        if let Some(tags) = tags {
+            entry.metadata.tags = tags.iter().cloned().collect();
+        }
+        entry.content = content;
+        Ok(())
+    }
+
+    fn change_tags(
+        &self,
+        id: &ItemId,
+        tag: &str,
+        change: impl FnOnce(&mut BTreeSet<String>) -> bool,
+    ) -> Result<bool, AppError> {
+        if !is_valid_tag(tag) {
+            return Err(AppError::config_error(format!("invalid tag: {tag}")));
+        }
+        Ok(change(&mut self.state().entry_mut(id)?.metadata.tags))
+    }
+}
+
+impl State {
+    fn entry(&self, id: &ItemId) -> Result<&Entry, AppError> {
+        self.items.get(id).ok_or_else(|| AppError::ItemNotFound(id.to_string()))
+    }
+
+    fn entry_mut(&mut self, id: &ItemId) -> Result<&mut Entry, AppError> {
+        self.items.get_mut(id).ok_or_else(|| AppError::ItemNotFound(id.to_string()))
+    }
+}
+
+/// Check that a new item can be placed at `id` among `items`, in the order
+/// [`crate::FilesystemStorage`] checks it: enclosing items, case collisions, then the id itself.
+pub(crate) fn ensure_placeable<V>(
+    items: &BTreeMap<ItemId, V>,
+    id: &ItemId,
+) -> Result<(), AppError> {
+    for parent in id.namespaces() {
+        if items.keys().any(|item| item == parent) {
+            return Err(AppError::config_error(format!(
+                "'{parent}' is an item and cannot hold '{id}'"
+            )));
+        }
+    }
+
+    let names: BTreeSet<&str> =
+        items.keys().flat_map(|item| item.namespaces().chain([item.as_str()])).collect();
+    for name in id.namespaces().chain([id.as_str()]) {
+        if names.contains(name) {
+            continue;
+        }
+        let folded = name.to_lowercase();
+        let existing = names
+            .iter()
+            .find(|other| other.to_lowercase() == folded && parent(other) == parent(name));
+        if let Some(existing) = existing {
+            return Err(AppError::CaseCollision(id.to_string(), existing.to_string()));
+        }
+        break;
+    }
+
+    if items.contains_key(id) {
+        return Err(AppError::ItemAlreadyExists(id.to_string()));
+    }
+    if names.contains(id.as_str()) {
+        return Err(AppError::config_error(format!("'{id}' is a namespace holding other items")));
+    }
+    Ok(())
+}
+
+/// Namespace directly enclosing `name`, or `""` at the top level.
+fn parent(name: &str) -> &str {
+    name.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn trash_entry(nanos: u128, id: &ItemId, entry: &Entry) -> TrashEntry {
+    TrashEntry {
+        id: id.to_string(),
+        deleted_at: (nanos / 1_000_000_000) as u64,
+        size: entry.content.len() as u64,
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn id_rules(&self) -> IdRules {
+        self.id_rules
+    }
+
+    fn add_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.insert(id, content.to_vec(), content_type, &[])
+    }
+
+    fn update_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.replace(id, |_| content.to_vec(), content_type, None)
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        self.replace(id, |current| [current, content].concat(), None, None)
+    }
+
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.insert(id, content, content_type, tags)
+    }
+
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.replace(id, |_| content, content_type, Some(tags))
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        Ok(self.state().entry(id)?.content.clone())
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        Ok(self.state().entry(id)?.metadata.clone())
+    }
+
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |tags| tags.insert(tag.to_string()))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |tags| tags.remove(tag))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        let state = self.state();
+        let entry = state.entry(id)?;
+        let mut versions: Vec<ItemMetadata> = entry
+            .versions
+            .iter()
+            .map(|(version, (_, metadata))| ItemMetadata { version: *version, ..metadata.clone() })
+            .collect();
+        versions.push(entry.metadata.clone());
+        Ok(versions)
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        let state = self.state();
+        let entry = state.entry(id)?;
+        let content = if version == entry.metadata.version {
+            &entry.content
+        } else {
+            &entry
+                .versions
+                .get(&version)
+                .ok_or_else(|| AppError::VersionNotFound(id.to_string(), version))?
+                .0
+        };
+        writer.write_all(content)?;
+        Ok(content.len() as u64)
+    }
+
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        Ok(self.state().items.keys().cloned().collect())
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut state = self.state();
+        let entry = state.items.remove(id).ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        // Keep deletions ordered even when the clock does not advance between them.
+        let nanos = state.trash.last().map_or(now, |(last, ..)| now.max(last + 1));
+        state.trash.push((nanos, id.clone(), entry));
+        Ok(())
+    }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut state = self.state();
+        state.items.remove(id).ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+        Ok(())
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        let state = self.state();
+        Ok(state.trash.iter().map(|(nanos, id, entry)| trash_entry(*nanos, id, entry)).collect())
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut state = self.state();
+        ensure_placeable(&state.items, id)?;
+        let position = state
+            .trash
+            .iter()
+            .rposition(|(_, trashed, _)| trashed == id)
+            .ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+        let (_, id, entry) = state.trash.remove(position);
+        state.items.insert(id, entry);
+        Ok(())
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        let cutoff = older_than.map(|age| unix_now().saturating_sub(age.as_secs()));
+        let mut state = self.state();
+        let mut removed = Vec::new();
+        state.trash.retain(|(nanos, id, entry)| {
+            let entry = trash_entry(*nanos, id, entry);
+            if cutoff.is_none_or(|cutoff| entry.deleted_at <= cutoff) {
+                removed.push(entry);
+                false
+            } else {
+                true
+            }
+        });
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::id;
+
+    #[test]
+    fn history_limit_bounds_retained_versions() {
+        let storage = MemoryStorage::new().with_history_limit(2);
+        storage.add_item(&id("note"), b"v1", None).unwrap();
+        for content in [b"v2", b"v3", b"v4"] {
+            storage.update_item(&id("note"), content, None).unwrap();
+        }
+
+        let versions = storage.list_versions(&id("note")).unwrap();
+        assert_eq!(versions.iter().map(|metadata| metadata.version).collect::<Vec<_>>(), [2, 3, 4]);
+
+        let storage = MemoryStorage::new().with_history_limit(0);
+        storage.add_item(&id("note"), b"v1", None).unwrap();
+        storage.update_item(&id("note"), b"v2", None).unwrap();
+        assert_eq!(storage.list_versions(&id("note")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn empty_trash_respects_age_cutoff() {
+        let storage = MemoryStorage::new();
+        storage.add_item(&id("old"), b"one", None).unwrap();
+        storage.add_item(&id("new"), b"two", None).unwrap();
+        storage.delete_item(&id("old")).unwrap();
+        storage.delete_item(&id("new")).unwrap();
+        // Backdate one deletion.
+        storage.state().trash[0].0 = 1_000_000_000_000_000_000;
+
+        let removed = storage.empty_trash(Some(Duration::from_secs(3600))).unwrap();
+        assert_eq!(removed.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["old"]);
+        assert_eq!(storage.list_trash().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Descriptive information persisted alongside each item's content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemMetadata {
+    /// Creation time in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// Time of the last content change in seconds since the Unix epoch.
+    pub updated_at: u64,
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// Hex-encoded SHA-256 digest of the content.
+    pub sha256: String,
+    /// Optional user-supplied media type such as `text/plain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Labels attached with `tag add` or `add --tag`, kept sorted.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Revision number of the content, starting at 1 and bumped on every change.
+    #[serde(default = "first_version")]
+    pub version: u64,
+}
+
+impl ItemMetadata {
+    /// Describe freshly created content.
+    #[cfg(test)]
+    pub(crate) fn new(content: &[u8], content_type: Option<&str>) -> Self {
+        Self::from_digest(ContentDigest::of(content), content_type)
+    }
+
+    /// Describe freshly created content whose digest was computed while streaming it.
+    pub(crate) fn from_digest(digest: ContentDigest, content_type: Option<&str>) -> Self {
+        let now = unix_now();
+        Self {
+            created_at: now,
+            updated_at: now,
+            size: digest.size,
+            sha256: digest.sha256,
+            content_type: content_type.map(str::to_string),
+            tags: BTreeSet::new(),
+            version: first_version(),
+        }
+    }
+
+    /// Record a content change as a new revision, keeping the creation time intact.
+    pub(crate) fn touch_digest(&mut self, digest: ContentDigest) {
+        self.updated_at = unix_now().max(self.created_at);
+        self.size = digest.size;
+        self.sha256 = digest.sha256;
+        self.version += 1;
+    }
+
+    /// Creation time as an RFC 3339 timestamp in UTC.
+    pub fn created_at_rfc3339(&self) -> String {
+        format_timestamp(self.created_at)
+    }
+
+    /// Last update time as an RFC 3339 timestamp in UTC.
+    pub fn updated_at_rfc3339(&self) -> String {
+        format_timestamp(self.updated_at)
+    }
+}
+
+/// An item moved to the trash by `delete`, restorable until the trash is emptied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// Deletion time in seconds since the Unix epoch.
+    pub deleted_at: u64,
+    /// Size of the deleted content in bytes.
+    pub size: u64,
+}
+
+impl TrashEntry {
+    /// Deletion time as an RFC 3339 timestamp in UTC.
+    pub fn deleted_at_rfc3339(&self) -> String {
+        format_timestamp(self.deleted_at)
+    }
+}
+
+fn first_version() -> u64 {
+    1
+}
+
+/// Size and hex-encoded SHA-256 digest of item content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ContentDigest {
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl ContentDigest {
+    pub(crate) fn of(content: &[u8]) -> Self {
+        Self { size: content.len() as u64, sha256: hex(&Sha256::digest(content)) }
+    }
+}
+
+/// Writer adapter that hashes and counts every byte on its way to `inner`.
+pub(crate) struct DigestWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> DigestWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, hasher: Sha256::new(), size: 0 }
+    }
+
+    /// Flush the inner writer and return it with the digest of everything written.
+    pub(crate) fn finish(mut self) -> io::Result<(W, ContentDigest)> {
+        self.inner.flush()?;
+        let digest = ContentDigest { size: self.size, sha256: hex(&self.hasher.finalize()) };
+        Ok((self.inner, digest))
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Whether `tag` can label an item: non-empty and made of alphanumerics, `-`, `_` or `.`.
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Reject malformed tags before anything is written.
+pub(crate) fn validate_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Result<(), AppError> {
+    match tags.into_iter().find(|tag| !is_valid_tag(tag)) {
+        Some(tag) => Err(AppError::config_error(format!("invalid tag: {tag}"))),
+        None => Ok(()),
+    }
+}
+
+/// Whether `content` should be treated as binary: not valid UTF-8 or containing NUL bytes.
+pub fn is_binary(content: &[u8]) -> bool {
+    content.contains(&0) || std::str::from_utf8(content).is_err()
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn format_timestamp(seconds: u64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(seconds)).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_metadata_describes_content() {
+        let metadata = ItemMetadata::new(b"hello", Some("text/plain"));
+
+        assert_eq!(metadata.size, 5);
+        assert_eq!(
+            metadata.sha256,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(metadata.created_at, metadata.updated_at);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(metadata.version, 1);
+    }
+
+    #[test]
+    fn touch_keeps_creation_time() {
+        let mut metadata = ItemMetadata::new(b"hello", None);
+        metadata.created_at = 1;
+
+        metadata.touch_digest(ContentDigest::of(b"hello world"));
+
+        assert_eq!(metadata.created_at, 1);
+        assert!(metadata.updated_at >= metadata.created_at);
+        assert_eq!(metadata.size, 11);
+        assert_eq!(metadata.version, 2);
+    }
+
+    #[test]
+    fn digest_writer_matches_buffered_digest() {
+        let mut writer = DigestWriter::new(Vec::new());
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+
+        let (written, digest) = writer.finish().unwrap();
+        assert_eq!(written, b"hello world");
+        assert_eq!(digest, ContentDigest::of(b"hello world"));
+    }
+
+    #[test]
+    fn is_binary_detects_nul_and_invalid_utf8() {
+        assert!(!is_binary("plain text ✓\n".as_bytes()));
+        assert!(is_binary(b"nul\0byte"));
+        assert!(is_binary(&[0xff, 0xfe, 0x41]));
+    }
+
+    #[test]
+    fn tags_are_omitted_from_json_when_empty() {
+        let mut metadata = ItemMetadata::new(b"", None);
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert!(json.get("tags").is_none());
+
+        metadata.tags.insert("work".to_string());
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["tags"], serde_json::json!(["work"]));
+        assert!(!is_valid_tag("has space") && !is_valid_tag("") && is_valid_tag("v1.2_rc-1"));
+    }
+
+    #[test]
+    fn timestamps_format_as_rfc3339() {
+        let mut metadata = ItemMetadata::new(b"", None);
+        metadata.created_at = 0;
+
+        assert_eq!(metadata.created_at_rfc3339(), "1970-01-01T00:00:00Z");
+    }
+}
@@ -0,0 +1,734 @@
+use crate::config::Source;
+use crate::error::AppError;
+use crate::index::IndexReport;
+use crate::logfile::CompactReport;
+use crate::metadata::{ItemMetadata, TrashEntry, is_binary};
+use crate::search::SearchMatch;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use clap::ValueEnum;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+
+/// Rendering used for everything a command prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Decorated, human-oriented text.
+    #[default]
+    Human,
+    /// One pretty-printed JSON document per event.
+    Json,
+    /// One compact JSON object per line; listings emit one line per item.
+    Ndjson,
+    /// Tab-separated values without decoration.
+    Tsv,
+}
+
+/// A single listed item, optionally carrying its metadata.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub metadata: Option<ItemMetadata>,
+}
+
+/// A configuration key with its effective value and the layer that supplied it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Option<String>,
+    pub source: Source,
+}
+
+/// A problem `doctor` found in the store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum Finding {
+    /// Names differing only in letter case, which share a directory on case-insensitive
+    /// filesystems.
+    CaseCollision { ids: Vec<String> },
+}
+
+/// Outcome of a command, rendered according to the selected [`OutputFormat`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    ItemAdded {
+        id: String,
+    },
+    ItemUpdated {
+        id: String,
+    },
+    ItemAppended {
+        id: String,
+    },
+    ItemUnchanged {
+        id: String,
+    },
+    /// An item permanently removed, bypassing the trash.
+    ItemDeleted {
+        id: String,
+    },
+    ItemTrashed {
+        id: String,
+    },
+    ItemRestored {
+        id: String,
+    },
+    /// A delete of an item, or of a namespace with `-r`, declined at the confirmation prompt.
+    DeleteAborted {
+        id: String,
+    },
+    ItemTagged {
+        id: String,
+        tag: String,
+    },
+    ItemUntagged {
+        id: String,
+        tag: String,
+    },
+    /// Lines matching a search query, in item then line order.
+    SearchResults {
+        query: String,
+        matches: Vec<SearchMatch>,
+    },
+    IndexRebuilt(IndexReport),
+    IndexVerified(IndexReport),
+    /// Sizes of the store log before and after `compact`.
+    StoreCompacted(CompactReport),
+    /// Problems found by `doctor`; empty when the store is healthy.
+    DoctorReport {
+        findings: Vec<Finding>,
+    },
+    /// Deleted items still held in the trash, oldest deletion first.
+    TrashListing {
+        entries: Vec<TrashEntry>,
+    },
+    /// Trashed items that were permanently removed.
+    TrashEmptied {
+        entries: Vec<TrashEntry>,
+    },
+    /// Raw item bytes; JSON carries text as `content` and binary data as `content_base64`.
+    ItemContent {
+        id: String,
+        #[serde(flatten, serialize_with = "serialize_content")]
+        content: Vec<u8>,
+    },
+    Listing {
+        items: Vec<ListEntry>,
+    },
+    /// Retained revisions of an item, oldest first.
+    ItemHistory {
+        id: String,
+        versions: Vec<ItemMetadata>,
+    },
+    ItemRolledBack {
+        id: String,
+        version: u64,
+    },
+    ConfigValue(ConfigEntry),
+    ConfigListing {
+        entries: Vec<ConfigEntry>,
+    },
+    ConfigSet {
+        key: String,
+        value: String,
+        path: String,
+    },
+    ConfigPath {
+        path: String,
+    },
+}
+
+/// Output sink that commands write their events to.
+pub trait Reporter {
+    fn report(&self, event: &Event);
+
+    /// Whether raw output would land on an interactive terminal.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    /// Writer that item content can be streamed into directly.
+    ///
+    /// Returning `None` (the default) makes commands buffer the content and report it as an
+    /// [`Event::ItemContent`] instead.
+    fn content_sink(&self) -> Option<Box<dyn Write + '_>> {
+        None
+    }
+}
+
+/// Writes command events to stdout and errors to stderr in the selected format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutReporter {
+    format: OutputFormat,
+    color: bool,
+    error_color: bool,
+}
+
+/// Discards every event; the natural choice for library callers that only need return values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentReporter;
+
+/// Records every event in memory so callers and tests can inspect what a command reported.
+#[derive(Debug, Default)]
+pub struct CaptureReporter {
+    events: RefCell<Vec<Event>>,
+}
+
+impl Reporter for StdoutReporter {
+    fn report(&self, event: &Event) {
+        let mut stdout = io::stdout().lock();
+        let _ = match (self.format, event) {
+            (OutputFormat::Human | OutputFormat::Tsv, Event::ItemContent { content, .. }) => {
+                stdout.write_all(content)
+            }
+            _ => stdout.write_all(self.render(event).as_bytes()),
+        };
+        let _ = stdout.flush();
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.format == OutputFormat::Human && io::stdout().is_terminal()
+    }
+
+    fn content_sink(&self) -> Option<Box<dyn Write + '_>> {
+        match self.format {
+            OutputFormat::Human | OutputFormat::Tsv => Some(Box::new(io::stdout().lock())),
+            OutputFormat::Json | OutputFormat::Ndjson => None,
+        }
+    }
+}
+
+impl Reporter for SilentReporter {
+    fn report(&self, _: &Event) {}
+}
+
+impl Reporter for CaptureReporter {
+    fn report(&self, event: &Event) {
+        self.events.borrow_mut().push(event.clone());
+    }
+}
+
+impl CaptureReporter {
+    /// Events reported so far, in order.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// Remove and return the events reported so far.
+    pub fn take(&self) -> Vec<Event> {
+        self.events.take()
+    }
+}
+
+impl StdoutReporter {
+    pub fn new(format: OutputFormat) -> Self {
+        Self { format, color: false, error_color: false }
+    }
+
+    /// Enable ANSI colors in human output on stdout.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Enable ANSI colors in human errors on stderr, which may be redirected apart from stdout.
+    pub fn with_error_color(mut self, color: bool) -> Self {
+        self.error_color = color;
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Print an error to stderr, as a structured object for machine formats.
+    pub fn error(&self, err: &AppError) {
+        eprint!("{}", self.render_error(err));
+    }
+
+    pub(crate) fn render(&self, event: &Event) -> String {
+        match self.format {
+            OutputFormat::Human => render_human(event, self.color),
+            OutputFormat::Json => to_json_line(event, true),
+            OutputFormat::Ndjson => match event {
+                Event::Listing { items } => {
+                    items.iter().map(|entry| to_json_line(entry, false)).collect()
+                }
+                Event::ConfigListing { entries } => {
+                    entries.iter().map(|entry| to_json_line(entry, false)).collect()
+                }
+                Event::ItemHistory { versions, .. } => {
+                    versions.iter().map(|version| to_json_line(version, false)).collect()
+                }
+                Event::TrashListing { entries } => {
+                    entries.iter().map(|entry| to_json_line(entry, false)).collect()
+                }
+                Event::SearchResults { matches, .. } => {
+                    matches.iter().map(|found| to_json_line(found, false)).collect()
+                }
+                Event::DoctorReport { findings } => {
+                    findings.iter().map(|finding| to_json_line(finding, false)).collect()
+                }
+                _ => to_json_line(event, false),
+            },
+            OutputFormat::Tsv => render_tsv(event),
+        }
+    }
+
+    pub(crate) fn render_error(&self, err: &AppError) -> String {
+        match self.format {
+            OutputFormat::Human => {
+                format!("{} {err}\n", paint("Error:", RED, self.error_color))
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                let value = json!({ "error": { "code": err.code(), "message": err.to_string() } });
+                to_json_line(&value, self.format == OutputFormat::Json)
+            }
+            OutputFormat::Tsv => {
+                format!("error\t{}\t{}\n", err.code(), escape_tsv(&err.to_string()))
+            }
+        }
+    }
+}
+
+fn render_human(event: &Event, color: bool) -> String {
+    let id = |id: &str| paint(id, BOLD, color);
+    match event {
+        Event::ItemAdded { id: item } => format!("✅ Added item '{}'\n", id(item)),
+        Event::ItemUpdated { id: item } => format!("✅ Updated item '{}'\n", id(item)),
+        Event::ItemAppended { id: item } => format!("✅ Appended to item '{}'\n", id(item)),
+        Event::ItemUnchanged { id: item } => format!("ℹ️  No changes to item '{}'\n", id(item)),
+        Event::ItemDeleted { id: item } => format!("🗑️  Deleted item '{}'\n", id(item)),
+        Event::ItemTrashed { id: item } => {
+            format!("🗑️  Deleted item '{}' (moved to the trash)\n", id(item))
+        }
+        Event::ItemRestored { id: item } => format!("♻️  Restored item '{}'\n", id(item)),
+        Event::DeleteAborted { id: item } => format!("ℹ️  Aborted, kept '{}'\n", id(item)),
+        Event::ItemTagged { id: item, tag } => {
+            format!("🏷️  Tagged item '{}' with '{tag}'\n", id(item))
+        }
+        Event::ItemUntagged { id: item, tag } => {
+            format!("🏷️  Removed tag '{tag}' from item '{}'\n", id(item))
+        }
+        Event::SearchResults { query, matches } => {
+            let mut text = match matches.len() {
+                0 => format!("🔎 No matches for '{query}'\n"),
+                1 => format!("🔎 1 match for '{query}':\n"),
+                count => format!("🔎 {count} matches for '{query}':\n"),
+            };
+            for found in matches {
+                text.push_str(&format!(
+                    "{}:{}: {}\n",
+                    id(&found.id),
+                    found.line,
+                    highlight(found, color)
+                ));
+            }
+            text
+        }
+        Event::IndexRebuilt(report) => {
+            format!("🔧 Rebuilt search index: {} items, {} terms\n", report.items, report.terms)
+        }
+        Event::IndexVerified(report) => format!(
+            "✅ Search index is up to date: {} items, {} terms\n",
+            report.items, report.terms
+        ),
+        Event::StoreCompacted(report) => {
+            format!("🗜️ Compacted the store log from {} to {} bytes\n", report.before, report.after)
+        }
+        Event::DoctorReport { findings } => {
+            let mut text = match findings.len() {
+                0 => String::from("🩺 No problems found\n"),
+                1 => String::from("🩺 Found 1 problem:\n"),
+                count => format!("🩺 Found {count} problems:\n"),
+            };
+            for finding in findings {
+                match finding {
+                    Finding::CaseCollision { ids } => text.push_str(&format!(
+                        "- names differ only in letter case: {}\n",
+                        ids.iter().map(|name| id(name)).collect::<Vec<_>>().join(", ")
+                    )),
+                }
+            }
+            text
+        }
+        Event::TrashListing { entries } => {
+            let mut text = String::from("🗑️  Trash:\n");
+            if entries.is_empty() {
+                text.push_str("(empty)\n");
+            }
+            for entry in entries {
+                text.push_str(&format!(
+                    "- {}  {} bytes  deleted {}\n",
+                    id(&entry.id),
+                    entry.size,
+                    entry.deleted_at_rfc3339(),
+                ));
+            }
+            text
+        }
+        Event::TrashEmptied { entries } => match entries.len() {
+            1 => String::from("🧹 Permanently removed 1 item from the trash\n"),
+            count => format!("🧹 Permanently removed {count} items from the trash\n"),
+        },
+        Event::ItemContent { content, .. } => String::from_utf8_lossy(content).into_owned(),
+        Event::Listing { items } => {
+            let mut text = String::from("📦 Stored items:\n");
+            if items.is_empty() {
+                text.push_str("(none)\n");
+            }
+            for entry in items {
+                match &entry.metadata {
+                    Some(metadata) => text.push_str(&format!(
+                        "- {id}  {size} bytes  updated {updated}  {content_type}{tags}\n",
+                        id = id(&entry.id),
+                        size = metadata.size,
+                        updated = metadata.updated_at_rfc3339(),
+                        content_type = metadata.content_type.as_deref().unwrap_or("-"),
+                        tags =
+                            metadata.tags.iter().map(|tag| format!("  #{tag}")).collect::<String>(),
+                    )),
+                    None => text.push_str(&format!("- {}\n", id(&entry.id))),
+                }
+            }
+            text
+        }
+        Event::ItemHistory { id: item, versions } => {
+            let mut text = format!("🕘 History of item '{}':\n", id(item));
+            for (index, metadata) in versions.iter().enumerate() {
+                let current = if index + 1 == versions.len() { "  (current)" } else { "" };
+                text.push_str(&format!(
+                    "- v{version}  {size} bytes  updated {updated}{current}\n",
+                    version = metadata.version,
+                    size = metadata.size,
+                    updated = metadata.updated_at_rfc3339(),
+                ));
+            }
+            text
+        }
+        Event::ItemRolledBack { id: item, version } => {
+            format!("⏪ Rolled back item '{}' to version {version}\n", id(item))
+        }
+        Event::ConfigValue(entry) => {
+            entry.value.as_ref().map(|value| format!("{value}\n")).unwrap_or_default()
+        }
+        Event::ConfigListing { entries } => entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} = {}  ({})\n",
+                    paint(&entry.key, BOLD, color),
+                    entry.value.as_deref().unwrap_or("(unset)"),
+                    entry.source
+                )
+            })
+            .collect(),
+        Event::ConfigSet { key, value, path } => {
+            format!("✅ Set {} = {value} in {path}\n", paint(key, BOLD, color))
+        }
+        Event::ConfigPath { path } => format!("{path}\n"),
+    }
+}
+
+fn render_tsv(event: &Event) -> String {
+    match event {
+        Event::ItemAdded { id } => format!("added\t{}\n", escape_tsv(id)),
+        Event::ItemUpdated { id } => format!("updated\t{}\n", escape_tsv(id)),
+        Event::ItemAppended { id } => format!("appended\t{}\n", escape_tsv(id)),
+        Event::ItemUnchanged { id } => format!("unchanged\t{}\n", escape_tsv(id)),
+        Event::ItemDeleted { id } => format!("deleted\t{}\n", escape_tsv(id)),
+        Event::ItemTrashed { id } => format!("trashed\t{}\n", escape_tsv(id)),
+        Event::ItemRestored { id } => format!("restored\t{}\n", escape_tsv(id)),
+        Event::DeleteAborted { id } => format!("aborted\t{}\n", escape_tsv(id)),
+        Event::ItemTagged { id, tag } => format!("tagged\t{}\t{tag}\n", escape_tsv(id)),
+        Event::ItemUntagged { id, tag } => format!("untagged\t{}\t{tag}\n", escape_tsv(id)),
+        Event::SearchResults { matches, .. } => matches
+            .iter()
+            .map(|found| {
+                format!("{}\t{}\t{}\n", escape_tsv(&found.id), found.line, escape_tsv(&found.text))
+            })
+            .collect(),
+        Event::IndexRebuilt(report) => format!("rebuilt\t{}\t{}\n", report.items, report.terms),
+        Event::IndexVerified(report) => format!("verified\t{}\t{}\n", report.items, report.terms),
+        Event::StoreCompacted(report) => {
+            format!("compacted\t{}\t{}\n", report.before, report.after)
+        }
+        Event::DoctorReport { findings } => findings
+            .iter()
+            .map(|finding| match finding {
+                Finding::CaseCollision { ids } => format!(
+                    "case_collision\t{}\n",
+                    ids.iter().map(|name| escape_tsv(name)).collect::<Vec<_>>().join("\t")
+                ),
+            })
+            .collect(),
+        Event::TrashListing { entries } => entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    escape_tsv(&entry.id),
+                    entry.size,
+                    entry.deleted_at_rfc3339()
+                )
+            })
+            .collect(),
+        Event::TrashEmptied { entries } => {
+            entries.iter().map(|entry| format!("purged\t{}\n", escape_tsv(&entry.id))).collect()
+        }
+        Event::ItemContent { content, .. } => String::from_utf8_lossy(content).into_owned(),
+        Event::ItemHistory { versions, .. } => versions
+            .iter()
+            .map(|metadata| {
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    metadata.version,
+                    metadata.size,
+                    metadata.updated_at_rfc3339(),
+                    metadata.sha256
+                )
+            })
+            .collect(),
+        Event::ItemRolledBack { id, version } => {
+            format!("rolled_back\t{}\t{version}\n", escape_tsv(id))
+        }
+        Event::ConfigValue(entry) => {
+            entry.value.as_ref().map(|value| format!("{}\n", escape_tsv(value))).unwrap_or_default()
+        }
+        Event::ConfigListing { entries } => entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    entry.key,
+                    escape_tsv(entry.value.as_deref().unwrap_or("")),
+                    entry.source
+                )
+            })
+            .collect(),
+        Event::ConfigSet { key, value, path } => {
+            format!("set\t{key}\t{}\t{}\n", escape_tsv(value), escape_tsv(path))
+        }
+        Event::ConfigPath { path } => format!("{}\n", escape_tsv(path)),
+        Event::Listing { items } => items
+            .iter()
+            .map(|entry| match &entry.metadata {
+                Some(metadata) => format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                    escape_tsv(&entry.id),
+                    metadata.size,
+                    metadata.created_at_rfc3339(),
+                    metadata.updated_at_rfc3339(),
+                    metadata.sha256,
+                    escape_tsv(metadata.content_type.as_deref().unwrap_or("")),
+                    metadata.tags.iter().map(String::as_str).collect::<Vec<_>>().join(","),
+                ),
+                None => format!("{}\n", escape_tsv(&entry.id)),
+            })
+            .collect(),
+    }
+}
+
+const BOLD: &str = "1";
+const RED: &str = "1;31";
+
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color { format!("\x1b[{code}m{text}\x1b[0m") } else { text.to_string() }
+}
+
+/// Shorten a matching line around its matches and paint each match.
+fn highlight(found: &SearchMatch, color: bool) -> String {
+    let (snippet, spans) = found.snippet();
+    let mut text = String::with_capacity(snippet.len());
+    let mut position = 0;
+    for (start, end) in spans {
+        text.push_str(&snippet[position..start]);
+        text.push_str(&paint(&snippet[start..end], RED, color));
+        position = end;
+    }
+    text.push_str(&snippet[position..]);
+    text
+}
+
+fn serialize_content<S: Serializer>(content: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(1))?;
+    match std::str::from_utf8(content) {
+        Ok(text) if !is_binary(content) => map.serialize_entry("content", text)?,
+        _ => map.serialize_entry("content_base64", &BASE64.encode(content))?,
+    }
+    map.end()
+}
+
+fn to_json_line<T: Serialize>(value: &T, pretty: bool) -> String {
+    let rendered =
+        if pretty { serde_json::to_string_pretty(value) } else { serde_json::to_string(value) };
+    let mut line = rendered.unwrap_or_else(|err| json!({ "error": err.to_string() }).to_string());
+    line.push('\n');
+    line
+}
+
+fn escape_tsv(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing() -> Event {
+        Event::Listing {
+            items: vec![
+                ListEntry { id: "first".to_string(), metadata: None },
+                ListEntry { id: "second".to_string(), metadata: None },
+            ],
+        }
+    }
+
+    #[test]
+    fn human_listing_matches_decorated_output() {
+        let rendered = StdoutReporter::new(OutputFormat::Human).render(&listing());
+        assert_eq!(rendered, "📦 Stored items:\n- first\n- second\n");
+
+        let empty = StdoutReporter::default().render(&Event::Listing { items: Vec::new() });
+        assert_eq!(empty, "📦 Stored items:\n(none)\n");
+    }
+
+    #[test]
+    fn json_renders_tagged_event() {
+        let rendered = StdoutReporter::new(OutputFormat::Json)
+            .render(&Event::ItemAdded { id: "demo".to_string() });
+
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value, json!({ "event": "item_added", "id": "demo" }));
+    }
+
+    #[test]
+    fn ndjson_emits_one_line_per_listed_item() {
+        let rendered = StdoutReporter::new(OutputFormat::Ndjson).render(&listing());
+        assert_eq!(rendered, "{\"id\":\"first\"}\n{\"id\":\"second\"}\n");
+    }
+
+    #[test]
+    fn tsv_escapes_separators() {
+        let rendered = StdoutReporter::new(OutputFormat::Tsv)
+            .render(&Event::ItemDeleted { id: "a\tb".to_string() });
+        assert_eq!(rendered, "deleted\ta\\tb\n");
+    }
+
+    #[test]
+    fn trash_listing_renders_per_format() {
+        let event = Event::TrashListing {
+            entries: vec![TrashEntry { id: "old".to_string(), deleted_at: 0, size: 3 }],
+        };
+
+        let human = StdoutReporter::new(OutputFormat::Human).render(&event);
+        assert_eq!(human, "🗑️  Trash:\n- old  3 bytes  deleted 1970-01-01T00:00:00Z\n");
+        let tsv = StdoutReporter::new(OutputFormat::Tsv).render(&event);
+        assert_eq!(tsv, "old\t3\t1970-01-01T00:00:00Z\n");
+        let ndjson = StdoutReporter::new(OutputFormat::Ndjson).render(&event);
+        assert_eq!(ndjson, "{\"id\":\"old\",\"deleted_at\":0,\"size\":3}\n");
+    }
+
+    #[test]
+    fn search_results_highlight_matches_when_colored() {
+        let event = Event::SearchResults {
+            query: "milk".to_string(),
+            matches: vec![SearchMatch {
+                id: "notes".to_string(),
+                line: 2,
+                text: "buy milk".to_string(),
+                spans: vec![(4, 8)],
+            }],
+        };
+
+        let plain = StdoutReporter::new(OutputFormat::Human).render(&event);
+        assert_eq!(plain, "🔎 1 match for 'milk':\nnotes:2: buy milk\n");
+        let colored = render_human(&event, true);
+        assert!(colored.ends_with("buy \x1b[1;31mmilk\x1b[0m\n"));
+        let tsv = StdoutReporter::new(OutputFormat::Tsv).render(&event);
+        assert_eq!(tsv, "notes\t2\tbuy milk\n");
+    }
+
+    #[test]
+    fn doctor_report_renders_per_format() {
+        let event = Event::DoctorReport {
+            findings: vec![Finding::CaseCollision { ids: vec!["Foo".into(), "foo".into()] }],
+        };
+
+        let human = StdoutReporter::new(OutputFormat::Human).render(&event);
+        assert_eq!(human, "🩺 Found 1 problem:\n- names differ only in letter case: Foo, foo\n");
+        let tsv = StdoutReporter::new(OutputFormat::Tsv).render(&event);
+        assert_eq!(tsv, "case_collision\tFoo\tfoo\n");
+        let ndjson = StdoutReporter::new(OutputFormat::Ndjson).render(&event);
+        assert_eq!(ndjson, "{\"problem\":\"case_collision\",\"ids\":[\"Foo\",\"foo\"]}\n");
+
+        let healthy = Event::DoctorReport { findings: Vec::new() };
+        let human = StdoutReporter::new(OutputFormat::Human).render(&healthy);
+        assert_eq!(human, "🩺 No problems found\n");
+    }
+
+    #[test]
+    fn json_encodes_binary_content_as_base64() {
+        let reporter = StdoutReporter::new(OutputFormat::Json);
+
+        let text = reporter
+            .render(&Event::ItemContent { id: "note".to_string(), content: b"hi".to_vec() });
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value, json!({ "event": "item_content", "id": "note", "content": "hi" }));
+
+        let binary = reporter
+            .render(&Event::ItemContent { id: "blob".to_string(), content: vec![0, 159, 255] });
+        let value: serde_json::Value = serde_json::from_str(&binary).unwrap();
+        assert_eq!(value["content_base64"], "AJ//");
+        assert!(value.get("content").is_none());
+    }
+
+    #[test]
+    fn machine_formats_render_structured_errors() {
+        let err = AppError::ItemNotFound("demo".to_string());
+
+        let rendered = StdoutReporter::new(OutputFormat::Ndjson).render_error(&err);
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["error"]["code"], "item_not_found");
+        assert_eq!(value["error"]["message"], "Item 'demo' was not found");
+
+        let tsv = StdoutReporter::new(OutputFormat::Tsv).render_error(&err);
+        assert_eq!(tsv, "error\titem_not_found\tItem 'demo' was not found\n");
+    }
+
+    #[test]
+    fn color_wraps_human_output_in_ansi_codes() {
+        let reporter = StdoutReporter::new(OutputFormat::Human).with_color(true);
+        let err = AppError::ItemNotFound("demo".to_string());
+
+        let rendered = reporter.render(&Event::ItemAdded { id: "demo".to_string() });
+        assert_eq!(rendered, "✅ Added item '\x1b[1mdemo\x1b[0m'\n");
+        assert!(reporter.render_error(&err).starts_with("Error: "));
+
+        let error = reporter.with_error_color(true).render_error(&err);
+        assert!(error.starts_with("\x1b[1;31mError:\x1b[0m "));
+    }
+
+    #[test]
+    fn capture_reporter_records_events_in_order() {
+        let reporter = CaptureReporter::default();
+
+        reporter.report(&Event::ItemAdded { id: "one".to_string() });
+        reporter.report(&Event::ItemDeleted { id: "one".to_string() });
+
+        assert_eq!(
+            reporter.take(),
+            vec![
+                Event::ItemAdded { id: "one".to_string() },
+                Event::ItemDeleted { id: "one".to_string() },
+            ]
+        );
+        assert!(reporter.events().is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::error::AppError;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Characters of context kept on either side of the matches when a line is shortened.
+const SNIPPET_CONTEXT: usize = 40;
+
+/// How a search query is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchMode {
+    /// Match letters regardless of case.
+    pub ignore_case: bool,
+    /// Treat the query as a regular expression instead of a literal substring.
+    pub regex: bool,
+}
+
+/// A line of an item containing at least one match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub id: String,
+    /// 1-based line number within the item.
+    pub line: usize,
+    /// The full matching line, without its terminator.
+    pub text: String,
+    /// Byte ranges of each match within `text`.
+    pub spans: Vec<(usize, usize)>,
+}
+
+impl SearchMatch {
+    /// The line shortened around its matches, with spans adjusted to the shortened text.
+    pub fn snippet(&self) -> (String, Vec<(usize, usize)>) {
+        let first = self.spans.first().map_or(0, |&(start, _)| start);
+        let start = floor_char_boundary(&self.text, first.saturating_sub(SNIPPET_CONTEXT));
+        let last = self.spans.last().map_or(first, |&(_, end)| end);
+        let end = ceil_char_boundary(&self.text, (last + SNIPPET_CONTEXT).min(self.text.len()));
+
+        let prefix = if start > 0 { "…" } else { "" };
+        let suffix = if end < self.text.len() { "…" } else { "" };
+        let shift = |index: usize| index - start + prefix.len();
+        let spans = self.spans.iter().map(|&(from, to)| (shift(from), shift(to))).collect();
+        (format!("{prefix}{}{suffix}", &self.text[start..end]), spans)
+    }
+}
+
+/// Compile `query` into a regex honouring `mode`; literal queries are escaped first.
+pub(crate) fn compile(query: &str, mode: SearchMode) -> Result<Regex, AppError> {
+    if query.is_empty() {
+        return Err(AppError::config_error("search query must not be empty"));
+    }
+
+    let pattern = if mode.regex { query.to_string() } else { regex::escape(query) };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(mode.ignore_case)
+        .build()
+        .map_err(|err| AppError::config_error(format!("invalid search pattern: {err}")))
+}
+
+/// Sink matching `pattern` against each line of item `id` as its content is written in.
+///
+/// Only the line being read is buffered, and a NUL byte ends buffering at once. Content that is
+/// not UTF-8 or holds a NUL byte marks the item as binary, and [`LineMatcher::finish`] then
+/// returns no matches for it.
+pub(crate) struct LineMatcher<'a> {
+    id: &'a str,
+    pattern: &'a Regex,
+    line: Vec<u8>,
+    number: usize,
+    binary: bool,
+    matches: Vec<SearchMatch>,
+}
+
+impl<'a> LineMatcher<'a> {
+    pub(crate) fn new(id: &'a str, pattern: &'a Regex) -> Self {
+        Self { id, pattern, line: Vec::new(), number: 0, binary: false, matches: Vec::new() }
+    }
+
+    /// The matching lines, in order, once every byte of the item has been written.
+    pub(crate) fn finish(mut self) -> Vec<SearchMatch> {
+        if !self.line.is_empty() {
+            self.end_line();
+        }
+        if self.binary { Vec::new() } else { self.matches }
+    }
+
+    /// Match the buffered line, split the way [`str::lines`] splits text.
+    fn end_line(&mut self) {
+        self.number += 1;
+        let line = match self.line.strip_suffix(b"\n") {
+            Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+            None => &self.line,
+        };
+        // Line feeds never occur inside a multi-byte character, so each line decodes alone.
+        match std::str::from_utf8(line) {
+            Ok(text) if !text.contains('\0') => {
+                let spans: Vec<_> =
+                    self.pattern.find_iter(text).map(|m| (m.start(), m.end())).collect();
+                if !spans.is_empty() {
+                    self.matches.push(SearchMatch {
+                        id: self.id.to_string(),
+                        line: self.number,
+                        text: text.to_string(),
+                        spans,
+                    });
+                }
+            }
+            _ => self.binary = true,
+        }
+        self.line.clear();
+    }
+}
+
+impl Write for LineMatcher<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.contains(&0) {
+            self.binary = true;
+            self.line = Vec::new();
+        }
+        if self.binary {
+            return Ok(buf.len());
+        }
+        for chunk in buf.split_inclusive(|&byte| byte == b'\n') {
+            self.line.extend_from_slice(chunk);
+            if chunk.ends_with(b"\n") {
+                self.end_line();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_queries_are_escaped() {
+        let pattern = compile("a.b", SearchMode::default()).unwrap();
+
+        assert!(pattern.is_match("xa.by"));
+        assert!(!pattern.is_match("axb"));
+    }
+
+    #[test]
+    fn modes_control_case_and_regex_syntax() {
+        let ignore_case = SearchMode { ignore_case: true, regex: false };
+        assert!(compile("TODO", ignore_case).unwrap().is_match("todo: tidy"));
+
+        let regex = SearchMode { ignore_case: false, regex: true };
+        assert!(compile(r"v\d+", regex).unwrap().is_match("release v12"));
+        assert!(matches!(compile("(", regex), Err(AppError::ConfigError(_))));
+        assert!(matches!(compile("", SearchMode::default()), Err(AppError::ConfigError(_))));
+    }
+
+    fn find_matches(chunks: &[&[u8]], pattern: &Regex) -> Vec<SearchMatch> {
+        let mut matcher = LineMatcher::new("demo", pattern);
+        for chunk in chunks {
+            matcher.write_all(chunk).unwrap();
+        }
+        matcher.finish()
+    }
+
+    #[test]
+    fn line_matcher_reports_lines_and_spans() {
+        let pattern = compile("o", SearchMode::default()).unwrap();
+
+        let matches = find_matches(&[b"one\r\ntw", b"o\nthree\nfo", b"ur"], &pattern);
+
+        let lines: Vec<usize> = matches.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 2, 4]);
+        assert_eq!(matches[0].spans, vec![(0, 1)]);
+        assert_eq!(matches[0].text, "one");
+        assert_eq!(matches[2].text, "four");
+    }
+
+    #[test]
+    fn line_matcher_drops_the_matches_of_binary_items() {
+        let pattern = compile("o", SearchMode::default()).unwrap();
+
+        assert!(find_matches(&[b"one\ntwo\n", b"nul\0"], &pattern).is_empty());
+        assert_eq!(find_matches(&[b"one\n\xe2\x9c", b"\x93 two\n"], &pattern).len(), 2);
+        assert!(find_matches(&[b"one\n\xff"], &pattern).is_empty());
+    }
+
+    #[test]
+    fn snippet_shortens_long_lines_around_matches() {
+        let text = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let found = SearchMatch { id: "demo".into(), line: 1, text, spans: vec![(100, 106)] };
+
+        let (snippet, spans) = found.snippet();
+
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        let (start, end) = spans[0];
+        assert_eq!(&snippet[start..end], "needle");
+        assert_eq!(snippet.chars().count(), 2 + 2 * SNIPPET_CONTEXT + 6);
+    }
+}
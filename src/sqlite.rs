@@ -0,0 +1,629 @@
+//! Storage backend keeping the whole store in one SQLite database.
+//!
+//! Compiled with the `sqlite` cargo feature, which bundles SQLite itself so no system library is
+//! needed. The schema is versioned with `PRAGMA user_version` and upgraded on open by applying
+//! the missing entries of [`MIGRATIONS`] in order; a database written by a newer schema is
+//! refused rather than guessed at.
+
+use crate::error::AppError;
+use crate::id::{IdRules, ItemId};
+use crate::metadata::{
+    ContentDigest, ItemMetadata, TrashEntry, is_valid_tag, unix_now, validate_tags,
+};
+use crate::storage::{DEFAULT_HISTORY_LIMIT, DEFAULT_LOCK_TIMEOUT, Storage};
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the database file `--backend sqlite` keeps in the storage root.
+pub const DATABASE_FILE_NAME: &str = "store.sqlite3";
+
+/// Schema changes, applied in order; the database's `user_version` counts those already applied.
+///
+/// Every item, live or trashed, is one `entries` row: `deleted_at` holds the deletion time in
+/// nanoseconds once it is in the trash. `folded` is the lowercased id used to find names that
+/// differ only in letter case, and `metadata` is the JSON the filesystem backend writes to
+/// `meta.json`. Archived revisions live in `versions` and follow their entry into the trash.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE entries (
+        entry INTEGER PRIMARY KEY AUTOINCREMENT,
+        id TEXT NOT NULL,
+        folded TEXT NOT NULL,
+        content BLOB NOT NULL,
+        metadata TEXT NOT NULL,
+        deleted_at INTEGER
+    );
+    CREATE UNIQUE INDEX live_ids ON entries (id) WHERE deleted_at IS NULL;
+    CREATE INDEX live_folded_ids ON entries (folded) WHERE deleted_at IS NULL;
+    CREATE INDEX trash_order ON entries (deleted_at) WHERE deleted_at IS NOT NULL;
+    CREATE TABLE versions (
+        entry INTEGER NOT NULL REFERENCES entries (entry) ON DELETE CASCADE,
+        version INTEGER NOT NULL,
+        content BLOB NOT NULL,
+        metadata TEXT NOT NULL,
+        PRIMARY KEY (entry, version)
+    );
+"];
+
+/// Storage backend keeping every item in a single SQLite database file.
+///
+/// It follows the same rules as [`crate::FilesystemStorage`]. The database runs in WAL mode, so
+/// readers in other processes are not blocked by a writer; writers wait up to the lock timeout
+/// for each other. Operations on one value are serialized by an internal lock, so a shared
+/// reference can be used from several threads.
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+    history_limit: usize,
+    id_rules: IdRules,
+}
+
+impl SqliteStorage {
+    /// Open the database at `path`, creating it and its parent directories if needed and
+    /// migrating it to the current schema.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut connection = Connection::open(path)?;
+        connection.busy_timeout(DEFAULT_LOCK_TIMEOUT)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.pragma_update(None, "synchronous", "FULL")?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut connection, path)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            id_rules: IdRules::default(),
+        })
+    }
+
+    /// Set how long to wait for another process's write to finish before giving up.
+    pub fn with_lock_timeout(self, timeout: Duration) -> Result<Self, AppError> {
+        self.connection().busy_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// Set how many prior revisions are kept per item; `0` disables history.
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set the rules identifiers given to this store's commands are checked against.
+    pub fn with_id_rules(mut self, rules: IdRules) -> Self {
+        self.id_rules = rules;
+        self
+    }
+
+    /// Schema version of the open database.
+    pub fn schema_version(&self) -> Result<usize, AppError> {
+        user_version(&self.connection())
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        // Transactions roll back when dropped, so a panic cannot leave a change half written.
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Run `change` in a transaction that takes the database write lock up front.
+    fn write<T>(
+        &self,
+        change: impl FnOnce(&Transaction<'_>) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let result = change(&transaction)?;
+        transaction.commit()?;
+        Ok(result)
+    }
+
+    fn insert(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        self.write(|transaction| {
+            ensure_placeable(transaction, id)?;
+            let mut metadata = ItemMetadata::from_digest(ContentDigest::of(content), content_type);
+            metadata.tags.extend(tags.iter().cloned());
+            transaction.execute(
+                "INSERT INTO entries (id, folded, content, metadata) VALUES (?1, ?2, ?3, ?4)",
+                (id.as_str(), id.to_lowercase(), content, encode(&metadata)?),
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Replace an item's content, archiving the current revision first, and its tags with `tags`
+    /// if given.
+    fn replace(
+        &self,
+        id: &ItemId,
+        change: impl FnOnce(Vec<u8>) -> Vec<u8>,
+        content_type: Option<&str>,
+        tags: Option<&[String]>,
+    ) -> Result<(), AppError> {
+        self.write(|transaction| {
+            let (entry, mut metadata) = live_entry(transaction, id)?;
+            let content = entry_content(transaction, entry)?;
+            if self.history_limit > 0 {
+                transaction.execute(
+                    "INSERT INTO versions (entry, version, content, metadata)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    (entry, metadata.version, &content, encode(&metadata)?),
+                )?;
+            }
+            transaction.execute(
+                "DELETE FROM versions WHERE entry = ?1 AND version NOT IN (
+                     SELECT version FROM versions WHERE entry = ?1
+                     ORDER BY version DESC LIMIT ?2
+                 )",
+                (entry, self.history_limit as i64),
+            )?;
+
+            let content = change(content);
+            metadata.touch_digest(ContentDigest::of(&content));
+            if let Some(content_type) = content_type {
+                metadata.content_type = Some(content_type.to_string());
+            }
+            if let Some(tags) = tags {
+                metadata.tags = tags.iter().cloned().collect();
+            }
+            transaction.execute(
+                "UPDATE entries SET content = ?2, metadata = ?3 WHERE entry = ?1",
+                (entry, &content, encode(&metadata)?),
+            )?;
+            Ok(())
+        })
+    }
+
+    fn change_tags(
+        &self,
+        id: &ItemId,
+        tag: &str,
+        change: impl FnOnce(&mut ItemMetadata) -> bool,
+    ) -> Result<bool, AppError> {
+        if !is_valid_tag(tag) {
+            return Err(AppError::config_error(format!("invalid tag: {tag}")));
+        }
+        self.write(|transaction| {
+            let (entry, mut metadata) = live_entry(transaction, id)?;
+            let changed = change(&mut metadata);
+            if changed {
+                transaction.execute(
+                    "UPDATE entries SET metadata = ?2 WHERE entry = ?1",
+                    (entry, encode(&metadata)?),
+                )?;
+            }
+            Ok(changed)
+        })
+    }
+}
+
+fn user_version(connection: &Connection) -> Result<usize, AppError> {
+    let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    Ok(version as usize)
+}
+
+/// Apply the migrations the database has not seen yet, each in its own transaction.
+fn migrate(connection: &mut Connection, path: &Path) -> Result<(), AppError> {
+    let applied = user_version(connection)?;
+    if applied > MIGRATIONS.len() {
+        return Err(AppError::config_error(format!(
+            "database {} uses schema version {applied}, newer than this build supports ({})",
+            path.display(),
+            MIGRATIONS.len()
+        )));
+    }
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        // Another process may have migrated while this one waited for the lock.
+        if user_version(&transaction)? > version {
+            continue;
+        }
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", version as i64 + 1)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn encode(metadata: &ItemMetadata) -> Result<String, AppError> {
+    Ok(serde_json::to_string(metadata).map_err(io::Error::from)?)
+}
+
+fn decode(metadata: &str) -> Result<ItemMetadata, AppError> {
+    Ok(serde_json::from_str(metadata).map_err(io::Error::from)?)
+}
+
+/// The row and metadata of a live item, leaving its content unread.
+fn live_entry(connection: &Connection, id: &ItemId) -> Result<(i64, ItemMetadata), AppError> {
+    let row = connection
+        .query_row(
+            "SELECT entry, metadata FROM entries WHERE id = ?1 AND deleted_at IS NULL",
+            [id.as_str()],
+            |row| Ok((row.get(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()?;
+    let (entry, metadata) = row.ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+    Ok((entry, decode(&metadata)?))
+}
+
+/// The current content of the row `entry`.
+fn entry_content(connection: &Connection, entry: i64) -> Result<Vec<u8>, AppError> {
+    Ok(connection
+        .query_row("SELECT content FROM entries WHERE entry = ?1", [entry], |row| row.get(0))?)
+}
+
+fn is_item(connection: &Connection, name: &str) -> Result<bool, AppError> {
+    Ok(connection
+        .query_row("SELECT 1 FROM entries WHERE id = ?1 AND deleted_at IS NULL", [name], |_| Ok(()))
+        .optional()?
+        .is_some())
+}
+
+/// Whether some live item sits below the namespace `name`.
+///
+/// Ids sort bytewise, and `0` follows `/`, so the items inside `name` are exactly those between
+/// `name/` and `name0`.
+fn is_namespace(connection: &Connection, name: &str) -> Result<bool, AppError> {
+    Ok(connection
+        .query_row(
+            "SELECT 1 FROM entries
+             WHERE id >= ?1 || '/' AND id < ?1 || '0' AND deleted_at IS NULL LIMIT 1",
+            [name],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Another spelling of `name` in the same namespace, differing only in letter case.
+fn case_variant(connection: &Connection, name: &str) -> Result<Option<String>, AppError> {
+    let mut statement = connection.prepare_cached(
+        "SELECT id FROM entries
+         WHERE (folded = ?1 OR (folded >= ?1 || '/' AND folded < ?1 || '0'))
+           AND deleted_at IS NULL",
+    )?;
+    let segments = name.split('/').count();
+    let mut rows = statement.query([name.to_lowercase()])?;
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(0)?;
+        let spelling = id.split('/').take(segments).collect::<Vec<_>>().join("/");
+        if spelling != name && parent(&spelling) == parent(name) {
+            return Ok(Some(spelling));
+        }
+    }
+    Ok(None)
+}
+
+/// Namespace directly enclosing `name`, or `""` at the top level.
+fn parent(name: &str) -> &str {
+    name.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// Check that a new item can be placed at `id`, in the order [`crate::FilesystemStorage`]
+/// checks it: enclosing items, case collisions, then the id itself.
+fn ensure_placeable(connection: &Connection, id: &ItemId) -> Result<(), AppError> {
+    for parent in id.namespaces() {
+        if is_item(connection, parent)? {
+            return Err(AppError::config_error(format!(
+                "'{parent}' is an item and cannot hold '{id}'"
+            )));
+        }
+    }
+
+    for name in id.namespaces().chain([id.as_str()]) {
+        if is_item(connection, name)? || is_namespace(connection, name)? {
+            continue;
+        }
+        if let Some(existing) = case_variant(connection, name)? {
+            return Err(AppError::CaseCollision(id.to_string(), existing));
+        }
+        break;
+    }
+
+    if is_item(connection, id)? {
+        return Err(AppError::ItemAlreadyExists(id.to_string()));
+    }
+    if is_namespace(connection, id)? {
+        return Err(AppError::config_error(format!("'{id}' is a namespace holding other items")));
+    }
+    Ok(())
+}
+
+fn trash_entry(id: String, deleted_at: i64, size: i64) -> TrashEntry {
+    TrashEntry { id, deleted_at: (deleted_at / 1_000_000_000) as u64, size: size as u64 }
+}
+
+impl Storage for SqliteStorage {
+    fn id_rules(&self) -> IdRules {
+        self.id_rules
+    }
+
+    fn add_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.insert(id, content, content_type, &[])
+    }
+
+    fn update_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.replace(id, |_| content.to_vec(), content_type, None)
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        self.replace(
+            id,
+            |mut current| {
+                current.extend_from_slice(content);
+                current
+            },
+            None,
+            None,
+        )
+    }
+
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.insert(id, &content, content_type, tags)
+    }
+
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.replace(id, |_| content, content_type, Some(tags))
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        let connection = self.connection();
+        let (entry, _) = live_entry(&connection, id)?;
+        entry_content(&connection, entry)
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        Ok(live_entry(&self.connection(), id)?.1)
+    }
+
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |metadata| metadata.tags.insert(tag.to_string()))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |metadata| metadata.tags.remove(tag))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        let connection = self.connection();
+        let (entry, current) = live_entry(&connection, id)?;
+        let mut statement = connection
+            .prepare_cached("SELECT metadata FROM versions WHERE entry = ?1 ORDER BY version")?;
+        let mut versions = statement
+            .query_map([entry], |row| row.get::<_, String>(0))?
+            .map(|metadata| decode(&metadata?))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        versions.push(current);
+        Ok(versions)
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        let connection = self.connection();
+        let (entry, metadata) = live_entry(&connection, id)?;
+        let content = if version == metadata.version {
+            entry_content(&connection, entry)?
+        } else {
+            connection
+                .query_row(
+                    "SELECT content FROM versions WHERE entry = ?1 AND version = ?2",
+                    (entry, version),
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| AppError::VersionNotFound(id.to_string(), version))?
+        };
+        writer.write_all(&content)?;
+        Ok(content.len() as u64)
+    }
+
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare_cached("SELECT id FROM entries WHERE deleted_at IS NULL ORDER BY id")?;
+        let ids = statement.query_map([], |row| row.get::<_, String>(0))?;
+        let mut items = Vec::new();
+        for id in ids {
+            // Rows written by hand with ids no rules accept are skipped, as stray directories
+            // are by the filesystem backend.
+            items.extend(ItemId::from_stored(&id?));
+        }
+        Ok(items)
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(|transaction| {
+            let (entry, _) = live_entry(transaction, id)?;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+            let last: Option<i64> =
+                transaction
+                    .query_row("SELECT max(deleted_at) FROM entries", [], |row| row.get(0))?;
+            // Keep deletions ordered even when the clock does not advance between them.
+            let deleted_at = last.map_or(now as i64, |last| (now as i64).max(last + 1));
+            transaction.execute(
+                "UPDATE entries SET deleted_at = ?2 WHERE entry = ?1",
+                (entry, deleted_at),
+            )?;
+            Ok(())
+        })
+    }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(|transaction| {
+            let (entry, _) = live_entry(transaction, id)?;
+            transaction.execute("DELETE FROM entries WHERE entry = ?1", [entry])?;
+            Ok(())
+        })
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        let connection = self.connection();
+        let mut statement = connection.prepare_cached(
+            "SELECT id, deleted_at, length(content) FROM entries
+             WHERE deleted_at IS NOT NULL ORDER BY deleted_at",
+        )?;
+        let entries = statement
+            .query_map([], |row| Ok(trash_entry(row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        Ok(entries.collect::<Result<_, _>>()?)
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(|transaction| {
+            ensure_placeable(transaction, id)?;
+            let restored = transaction.execute(
+                "UPDATE entries SET deleted_at = NULL WHERE entry = (
+                     SELECT entry FROM entries WHERE id = ?1 AND deleted_at IS NOT NULL
+                     ORDER BY deleted_at DESC LIMIT 1
+                 )",
+                [id.as_str()],
+            )?;
+            if restored == 0 {
+                return Err(AppError::ItemNotFound(id.to_string()));
+            }
+            Ok(())
+        })
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        let cutoff = older_than.map(|age| unix_now().saturating_sub(age.as_secs()));
+        self.write(|transaction| {
+            let mut removed = transaction
+                .prepare(
+                    "DELETE FROM entries
+                     WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at / 1000000000 <= ?1)
+                     RETURNING id, deleted_at, length(content)",
+                )?
+                .query_map([cutoff.map(|cutoff| cutoff as i64)], |row| {
+                    Ok(trash_entry(row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            removed.sort_by_key(|entry| entry.deleted_at);
+            Ok(removed)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::id;
+    use tempfile::TempDir;
+
+    fn open() -> (TempDir, SqliteStorage) {
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let storage = SqliteStorage::open(dir.path().join(DATABASE_FILE_NAME)).unwrap();
+        (dir, storage)
+    }
+
+    #[test]
+    fn open_migrates_to_the_current_schema_in_wal_mode() {
+        let (dir, storage) = open();
+        assert_eq!(storage.schema_version().unwrap(), MIGRATIONS.len());
+        let mode: String = storage
+            .connection()
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        storage.add_item(&id("note"), b"kept", None).unwrap();
+        drop(storage);
+
+        let reopened = SqliteStorage::open(dir.path().join(DATABASE_FILE_NAME)).unwrap();
+        assert_eq!(reopened.get_item(&id("note")).unwrap(), b"kept");
+    }
+
+    #[test]
+    fn open_refuses_databases_from_newer_schemas() {
+        let (dir, storage) = open();
+        storage.connection().pragma_update(None, "user_version", 99).unwrap();
+        drop(storage);
+
+        let result = SqliteStorage::open(dir.path().join(DATABASE_FILE_NAME));
+        assert!(
+            matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("99"))
+        );
+    }
+
+    #[test]
+    fn history_limit_bounds_retained_versions() {
+        let (_dir, storage) = open();
+        let storage = storage.with_history_limit(2);
+        storage.add_item(&id("note"), b"v1", None).unwrap();
+        for content in [b"v2", b"v3", b"v4"] {
+            storage.update_item(&id("note"), content, None).unwrap();
+        }
+
+        let versions = storage.list_versions(&id("note")).unwrap();
+        assert_eq!(versions.iter().map(|metadata| metadata.version).collect::<Vec<_>>(), [2, 3, 4]);
+
+        storage.delete_item(&id("note")).unwrap();
+        storage.restore_item(&id("note")).unwrap();
+        assert_eq!(storage.list_versions(&id("note")).unwrap().len(), 3);
+        storage.purge_item(&id("note")).unwrap();
+        let orphans: i64 = storage
+            .connection()
+            .query_row("SELECT count(*) FROM versions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orphans, 0);
+    }
+
+    #[test]
+    fn empty_trash_respects_age_cutoff() {
+        let (_dir, storage) = open();
+        storage.add_item(&id("old"), b"one", None).unwrap();
+        storage.add_item(&id("new"), b"two", None).unwrap();
+        storage.delete_item(&id("old")).unwrap();
+        storage.delete_item(&id("new")).unwrap();
+        // Backdate one deletion.
+        storage
+            .connection()
+            .execute("UPDATE entries SET deleted_at = 1000000000 WHERE id = 'old'", [])
+            .unwrap();
+
+        let removed = storage.empty_trash(Some(Duration::from_secs(3600))).unwrap();
+        assert_eq!(removed.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["old"]);
+        assert_eq!(storage.list_trash().unwrap().len(), 1);
+    }
+}
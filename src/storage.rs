@@ -1,180 +1,2123 @@
+use crate::config::CONFIG_FILE_NAME;
 use crate::error::AppError;
-use std::fs;
-use std::path::{Component, Path, PathBuf};
+use crate::id::{IdRules, ItemId, SEPARATOR};
+use crate::index::{IndexReport, Postings, SearchIndex, terms_of, terms_of_file};
+use crate::metadata::{
+    ContentDigest, DigestWriter, ItemMetadata, TrashEntry, is_valid_tag, unix_now, validate_tags,
+};
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub(crate) trait Storage {
-    fn add_item(&self, id: &str, content: &str) -> Result<(), AppError>;
-    fn list_items(&self) -> Result<Vec<String>, AppError>;
-    fn delete_item(&self, id: &str) -> Result<(), AppError>;
+/// Operations every storage backend provides to the commands.
+///
+/// Identifiers arrive already checked against the caller's [`IdRules`]. Backends reject names
+/// that would overlap an existing item or namespace, including by letter case, and report absent
+/// items with `ItemNotFound`; [`crate::conformance`] (behind the `testing` feature) checks these
+/// rules for any implementation.
+pub trait Storage {
+    /// Rules identifiers are checked against before commands pass them to this backend.
+    fn id_rules(&self) -> IdRules {
+        IdRules::default()
+    }
+    /// Create a new item, failing with `ItemAlreadyExists` if the id is taken.
+    fn add_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError>;
+    /// Replace the content of an existing item, failing with `ItemNotFound` if absent.
+    ///
+    /// A `content_type` of `None` keeps the previously recorded type.
+    fn update_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError>;
+    /// Append content to the end of an existing item, failing with `ItemNotFound` if absent.
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError>;
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError>;
+    /// Create a new item from a stream.
+    ///
+    /// The default buffers the whole stream; backends should override it to copy in chunks.
+    fn add_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.add_item(id, &content, content_type)
+    }
+    /// Replace the content of an existing item from a stream.
+    ///
+    /// The default buffers the whole stream; backends should override it to copy in chunks.
+    fn update_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        self.update_item(id, &content, content_type)
+    }
+    /// Create a new item from a stream with `tags` attached in the same write, so the item is
+    /// never stored without them.
+    ///
+    /// The default attaches the tags after storing the content, so a failure can leave the item
+    /// untagged; backends should override it to write both at once.
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        self.add_item_from_reader(id, reader, content_type)?;
+        for tag in tags {
+            self.add_tag(id, tag)?;
+        }
+        Ok(())
+    }
+    /// Replace the content of an existing item from a stream and its tags with `tags`, in the
+    /// same write, so an overwritten item carries only the tags it was written with.
+    ///
+    /// The default changes the tags after storing the content; backends should override it.
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let previous = self.item_metadata(id)?.tags;
+        self.update_item_from_reader(id, reader, content_type)?;
+        for tag in previous.iter().filter(|tag| !tags.contains(tag)) {
+            self.remove_tag(id, tag)?;
+        }
+        for tag in tags {
+            self.add_tag(id, tag)?;
+        }
+        Ok(())
+    }
+    /// Copy an item's content into `writer`, returning the number of bytes written.
+    fn read_item_into(&self, id: &ItemId, writer: &mut dyn Write) -> Result<u64, AppError> {
+        let content = self.get_item(id)?;
+        writer.write_all(&content)?;
+        Ok(content.len() as u64)
+    }
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError>;
+    /// Attach `tag` to an existing item, returning `false` if it was already attached.
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError>;
+    /// Detach `tag` from an existing item, returning `false` if it was not attached.
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError>;
+    /// Every retained revision of an item, oldest first; the last entry describes the current
+    /// content.
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError>;
+    /// Copy a retained revision of an item into `writer`, returning the number of bytes written.
+    ///
+    /// Fails with `VersionNotFound` if the revision never existed or was pruned.
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError>;
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError>;
+    /// Ids of the items that may contain `query` as a case-insensitive substring, in id order.
+    ///
+    /// Candidates may include items that do not match, but never omit one that does. `None`
+    /// means the backend keeps no index able to answer and every item must be scanned.
+    fn search_candidates(&self, _query: &str) -> Result<Option<Vec<ItemId>>, AppError> {
+        Ok(None)
+    }
+    /// Move an item to the trash, failing with `ItemNotFound` if absent.
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError>;
+    /// Permanently remove an item without going through the trash.
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError>;
+    /// Items currently in the trash, oldest deletion first.
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError>;
+    /// Bring the most recently trashed copy of an item back.
+    ///
+    /// Fails with `ItemAlreadyExists` if the id has been reused since, or `ItemNotFound` if the
+    /// trash holds no copy of it.
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError>;
+    /// Permanently remove trashed items deleted at least `older_than` ago, or all of them when
+    /// `None`, returning the entries removed.
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError>;
 }
 
+/// Environment variable that overrides the storage root directory.
+pub const STORE_ENV_VAR: &str = "RS_CLI_TMPL_HOME";
+
+const APP_DIR_NAME: &str = "rs-cli-tmpl";
+
+/// Buffer size used when streaming content to disk.
+const WRITE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Name prefix of scratch entries that are renamed into place once fully written.
+///
+/// The leading dot keeps them out of listings and can never start a valid identifier.
+const TEMP_PREFIX: &str = ".tmp-";
+
+const ITEM_FILE_NAME: &str = "item.txt";
+const METADATA_FILE_NAME: &str = "meta.json";
+
+/// Suffix naming the metadata staged alongside replacement content, `<staged content>.json`.
+///
+/// Content is renamed into place first, so such a file whose content partner is gone holds the
+/// metadata of the committed content and is rolled forward by recovery.
+const STAGED_METADATA_SUFFIX: &str = ".json";
+
+/// Directory inside each item holding `<version>.txt` and `<version>.json` for prior revisions.
+const VERSIONS_DIR_NAME: &str = "versions";
+
+/// Directory of deleted items, each kept as `<deletion nanos>-<id>/` until the trash is emptied.
+///
+/// Namespace separators in the id are written as [`FLAT_SEPARATOR`] to keep the trash flat.
+const TRASH_DIR_NAME: &str = ".trash";
+
+/// Stand-in for [`SEPARATOR`] in trash entry and item lock file names; never valid in an id.
+const FLAT_SEPARATOR: char = '%';
+
+/// Directory holding the optional search index; see [`crate::index`].
+const INDEX_DIR_NAME: &str = ".index";
+
+/// Number of prior revisions kept per item unless configured otherwise.
+pub const DEFAULT_HISTORY_LIMIT: usize = 10;
+
+/// How long to wait for another process to release a lock before giving up.
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Store-wide lock file; taken exclusively by mutations and shared by listings.
+const STORE_LOCK_FILE_NAME: &str = ".lock";
+
+/// Directory of per-item lock files, shared by readers and taken exclusively by writers.
+///
+/// Each is named after its id with separators written as [`FLAT_SEPARATOR`], so an id such as
+/// `a.lock/b` cannot claim the path of another id's lock file.
+const ITEM_LOCKS_DIR_NAME: &str = ".locks";
+
+/// Storage backend keeping each item in `<root>/<id>/item.txt`.
+///
+/// Concurrent processes are coordinated with advisory file locks: mutations hold the store lock
+/// exclusively and the item's lock exclusively, reads hold the item's lock shared, and listings
+/// hold the store lock shared.
 #[derive(Debug, Clone)]
-pub(crate) struct FilesystemStorage {
+pub struct FilesystemStorage {
     root_path: PathBuf,
+    lock_timeout: Duration,
+    history_limit: usize,
+    id_rules: IdRules,
+    /// Resolve item paths ignoring letter case, as macOS and Windows volumes do; set by tests to
+    /// reproduce those filesystems on Linux.
+    case_insensitive_paths: bool,
+}
+
+/// Whether a lock may be held alongside other holders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// Advisory lock held until dropped.
+pub(crate) struct LockGuard {
+    file: File,
+}
+
+impl LockGuard {
+    /// Whether the locked file is still the one at `path`, which a delete may have unlinked
+    /// while this lock was awaited.
+    #[cfg(unix)]
+    fn holds(&self, path: &Path) -> io::Result<bool> {
+        use std::os::unix::fs::MetadataExt;
+
+        let held = self.file.metadata()?;
+        match fs::metadata(path) {
+            Ok(current) => Ok(current.dev() == held.dev() && current.ino() == held.ino()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Item lock files are never unlinked on this platform, so a held lock is always current.
+    #[cfg(not(unix))]
+    fn holds(&self, _: &Path) -> io::Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Index removals held back until the change they describe is visible.
+#[derive(Default)]
+struct Reindex {
+    pending: Option<(SearchIndex, String, BTreeSet<String>)>,
+}
+
+impl Reindex {
+    fn finish(self) -> Result<(), AppError> {
+        if let Some((index, id, removed)) = self.pending {
+            index.record(&id, &BTreeSet::new(), &removed)?;
+        }
+        Ok(())
+    }
 }
 
 impl FilesystemStorage {
+    /// Create a storage rooted at an arbitrary directory; it is created lazily on first write.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root_path: root.into(),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            id_rules: IdRules::default(),
+            case_insensitive_paths: false,
+        }
+    }
+
+    /// Set how long operations wait for locks held by other processes.
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Create a storage rooted at the directory resolved by [`FilesystemStorage::default_root`].
     pub fn new_default() -> Result<Self, AppError> {
-        let home = std::env::var("HOME")
-            .map_err(|_| AppError::config_error("HOME environment variable not set"))?;
-        Ok(Self { root_path: PathBuf::from(home).join(".config").join("rs-cli-tmpl") })
+        Ok(Self::new(Self::default_root()?))
     }
 
-    fn ensure_valid_id(&self, id: &str) -> Result<(), AppError> {
-        if Self::is_id_valid(id) {
-            Ok(())
-        } else {
-            Err(AppError::config_error(format!("invalid item identifier: {id}")))
+    /// Set how many prior revisions are kept per item; `0` disables history.
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// Set the rules identifiers given to this store's commands are checked against.
+    pub fn with_id_rules(mut self, rules: IdRules) -> Self {
+        self.id_rules = rules;
+        self
+    }
+
+    /// Resolve the storage root from the environment, in order of precedence:
+    ///
+    /// 1. `$RS_CLI_TMPL_HOME`
+    /// 2. `$XDG_DATA_HOME/rs-cli-tmpl`
+    /// 3. `$XDG_CONFIG_HOME/rs-cli-tmpl`
+    /// 4. `$HOME/.config/rs-cli-tmpl`
+    ///
+    /// Empty variables are treated as unset. A store left at `$HOME/.config/rs-cli-tmpl` by
+    /// releases that always used it is kept in use until one of the XDG locations holds a store.
+    pub fn default_root() -> Result<PathBuf, AppError> {
+        resolve_root(|name| env::var_os(name))
+    }
+
+    /// Directory holding this storage's items.
+    pub fn root(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Emulate a case-insensitive filesystem, so `Foo` and `foo` resolve to the same directory.
+    #[cfg(test)]
+    pub(crate) fn with_case_insensitive_paths(mut self) -> Self {
+        self.case_insensitive_paths = true;
+        self
+    }
+
+    /// Check that a new item can be placed at `id`: no enclosing namespace is itself an item, no
+    /// existing entry differs from it only in case, and the id is free rather than a namespace
+    /// holding other items.
+    fn ensure_placeable(&self, id: &ItemId) -> Result<(), AppError> {
+        for parent in id.namespaces() {
+            if self.item_file(parent).is_file() {
+                return Err(AppError::config_error(format!(
+                    "'{parent}' is an item and cannot hold '{id}'"
+                )));
+            }
+        }
+        self.ensure_no_case_collision(id)?;
+        let directory = self.item_dir(id);
+        if directory.exists() {
+            if self.item_file(id).is_file() {
+                return Err(AppError::ItemAlreadyExists(id.to_string()));
+            }
+            if fs::read_dir(&directory)?.next().is_some() {
+                return Err(AppError::config_error(format!(
+                    "'{id}' is a namespace holding other items"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail with `CaseCollision` if `id` or one of its namespaces is stored only under another
+    /// spelling, such as `Team` for `team/readme`.
+    fn ensure_no_case_collision(&self, id: &ItemId) -> Result<(), AppError> {
+        let mut directory = self.root_path.clone();
+        let mut prefix = String::new();
+        for segment in id.split(SEPARATOR) {
+            let names = match fs::read_dir(&directory) {
+                Ok(entries) => entries
+                    .map(|entry| entry.map(|entry| entry.file_name()))
+                    .collect::<io::Result<Vec<_>>>()?,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            if !names.iter().any(|name| name == segment) {
+                let folded = segment.to_lowercase();
+                let existing = names
+                    .iter()
+                    .filter_map(|name| name.to_str())
+                    .find(|name| name.to_lowercase() == folded);
+                return match existing {
+                    Some(name) => {
+                        Err(AppError::CaseCollision(id.to_string(), format!("{prefix}{name}")))
+                    }
+                    None => Ok(()),
+                };
+            }
+            directory.push(segment);
+            prefix.push_str(segment);
+            prefix.push(SEPARATOR);
+        }
+        Ok(())
+    }
+
+    /// Whether the filesystem holding the store ignores letter case, probed through the store
+    /// lock file; the caller holds the store lock, so the file exists.
+    fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive_paths
+            || self.root_path.join(STORE_LOCK_FILE_NAME.to_uppercase()).exists()
+    }
+
+    /// Create the namespace directories enclosing `id`, replacing a leftover empty directory at
+    /// its own path so the item can be renamed into place.
+    fn prepare_item_dir(&self, id: &str) -> Result<PathBuf, AppError> {
+        let directory = self.item_dir(id);
+        if let Some(parent) = directory.parent() {
+            fs::create_dir_all(parent)?;
         }
+        if directory.is_dir() {
+            fs::remove_dir(&directory)?;
+        }
+        Ok(directory)
     }
 
-    fn is_id_valid(id: &str) -> bool {
-        !id.is_empty()
-            && id.chars().all(|c| c.is_alphanumeric() || c == '-')
-            && Path::new(id).components().all(|c| matches!(c, Component::Normal(_)))
+    /// Remove the namespace directories left empty once `id` is gone, innermost first.
+    fn prune_namespaces(&self, id: &ItemId) {
+        for namespace in id.namespaces().rev() {
+            // Fails once a namespace still holds other items, which ends the walk.
+            if fs::remove_dir(self.item_dir(namespace)).is_err() {
+                break;
+            }
+        }
     }
 
     fn item_dir(&self, id: &str) -> PathBuf {
+        if self.case_insensitive_paths {
+            return resolve_ignoring_case(&self.root_path, id);
+        }
         self.root_path.join(id)
     }
 
     fn item_file(&self, id: &str) -> PathBuf {
-        self.item_dir(id).join("item.txt")
+        self.item_dir(id).join(ITEM_FILE_NAME)
     }
-}
 
-impl Storage for FilesystemStorage {
-    fn add_item(&self, id: &str, content: &str) -> Result<(), AppError> {
-        self.ensure_valid_id(id)?;
-        let directory = self.item_dir(id);
-        fs::create_dir_all(&directory)?;
-        fs::write(self.item_file(id), content)?;
-        Ok(())
+    fn metadata_file(&self, id: &str) -> PathBuf {
+        self.item_dir(id).join(METADATA_FILE_NAME)
     }
 
-    fn list_items(&self) -> Result<Vec<String>, AppError> {
-        if !self.root_path.exists() {
+    fn versions_dir(&self, id: &str) -> PathBuf {
+        self.item_dir(id).join(VERSIONS_DIR_NAME)
+    }
+
+    fn version_file(&self, id: &str, version: u64, extension: &str) -> PathBuf {
+        self.versions_dir(id).join(format!("{version}.{extension}"))
+    }
+
+    fn trash_dir(&self) -> PathBuf {
+        self.root_path.join(TRASH_DIR_NAME)
+    }
+
+    /// Trashed items with the directory holding each, oldest deletion first.
+    fn trash_entries(&self) -> Result<Vec<(TrashEntry, PathBuf)>, AppError> {
+        let trash = self.trash_dir();
+        if !trash.is_dir() {
             return Ok(Vec::new());
         }
 
-        let mut ids = Vec::new();
-        for entry in fs::read_dir(&self.root_path)? {
-            let entry = entry?;
-            if entry.path().is_dir()
-                && let Some(name) = entry.file_name().to_str()
-            {
-                ids.push(name.to_string());
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&trash)? {
+            let path = entry?.path();
+            let Some((nanos, id)) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split_once('-'))
+                .and_then(|(nanos, id)| Some((nanos.parse::<u128>().ok()?, id)))
+            else {
+                continue;
+            };
+            let size = fs::metadata(path.join(ITEM_FILE_NAME)).map_or(0, |meta| meta.len());
+            let deleted_at = (nanos / 1_000_000_000) as u64;
+            let id = id.replace(FLAT_SEPARATOR, &SEPARATOR.to_string());
+            entries.push((nanos, TrashEntry { id, deleted_at, size }, path));
+        }
+
+        entries.sort_by(|a, b| (a.0, &a.1.id).cmp(&(b.0, &b.1.id)));
+        Ok(entries.into_iter().map(|(_, entry, path)| (entry, path)).collect())
+    }
+
+    /// Remove a directory for good, moving it out of sight first so an interrupted removal
+    /// never leaves half an item behind.
+    fn discard(&self, directory: &Path) -> Result<(), AppError> {
+        let staged = Staged::new(&self.root_path);
+        fs::rename(directory, &staged.path)?;
+        sync_dir(&self.root_path)?;
+        drop(staged);
+        Ok(())
+    }
+
+    /// Whether this store keeps a search index, created by [`FilesystemStorage::rebuild_index`].
+    pub fn has_index(&self) -> bool {
+        self.search_index().exists()
+    }
+
+    /// Build the search index from the content of every item, creating it if missing.
+    ///
+    /// Once it exists, the index is kept up to date by every write and used by searches for
+    /// literal text; rebuilding also compacts the postings appended since the last rebuild.
+    pub fn rebuild_index(&self) -> Result<IndexReport, AppError> {
+        fs::create_dir_all(&self.root_path)?;
+        let _store = self.lock_store(LockMode::Exclusive)?;
+        let postings = self.collect_postings()?;
+
+        let staged = Staged::new(&self.root_path);
+        fs::create_dir(&staged.path)?;
+        self.search_index().write_shards(&staged.path, &postings)?;
+        sync_dir(&staged.path)?;
+        let target = self.root_path.join(INDEX_DIR_NAME);
+        let previous = Staged::new(&self.root_path);
+        if target.exists() {
+            fs::rename(&target, &previous.path)?;
+        }
+        staged.commit(&target)?;
+        drop(previous);
+
+        Ok(IndexReport {
+            items: postings.ids.len(),
+            terms: postings.terms.len(),
+            ..Default::default()
+        })
+    }
+
+    /// Compare the search index with the content of every item without changing anything.
+    pub fn verify_index(&self) -> Result<IndexReport, AppError> {
+        let index = self.search_index();
+        if !index.exists() {
+            return Err(AppError::config_error(
+                "this store has no search index; run `index rebuild` to create one",
+            ));
+        }
+
+        let _store = self.lock_store(LockMode::Shared)?;
+        Ok(index.verify(&self.collect_postings()?)?)
+    }
+
+    fn search_index(&self) -> SearchIndex {
+        SearchIndex::new(self.root_path.join(INDEX_DIR_NAME))
+    }
+
+    /// Terms of every text item; the caller holds the store lock.
+    fn collect_postings(&self) -> Result<Postings, AppError> {
+        let mut postings = Postings::default();
+        for id in self.item_ids()? {
+            if let Some(terms) = terms_of_file(&self.item_file(&id))? {
+                postings.insert(&id, terms);
             }
         }
+        Ok(postings)
+    }
+
+    /// Index the terms of the content at `after` that `id` did not have at `before`.
+    ///
+    /// The terms it loses are only removed by [`Reindex::finish`], once the new content is in
+    /// place. Does nothing when the store keeps no index.
+    fn reindex(
+        &self,
+        id: &str,
+        before: Option<&Path>,
+        after: Option<&Path>,
+    ) -> Result<Reindex, AppError> {
+        let index = self.search_index();
+        if !index.exists() {
+            return Ok(Reindex::default());
+        }
+
+        let terms = |path: Option<&Path>| match path.filter(|path| path.is_file()) {
+            Some(path) => terms_of_file(path).map(Option::unwrap_or_default),
+            None => Ok(BTreeSet::new()),
+        };
+        let (old, new) = (terms(before)?, terms(after)?);
+        index.record(id, &new.difference(&old).cloned().collect(), &BTreeSet::new())?;
+        let removed = old.difference(&new).cloned().collect();
+        Ok(Reindex { pending: Some((index, id.to_string(), removed)) })
+    }
 
+    /// Identifiers of live items in every namespace, sorted; the caller holds the store lock.
+    fn item_ids(&self) -> Result<Vec<ItemId>, AppError> {
+        let mut ids = Vec::new();
+        collect_ids(&self.root_path, "", &mut ids)?;
         ids.sort();
         Ok(ids)
     }
 
-    fn delete_item(&self, id: &str) -> Result<(), AppError> {
-        self.ensure_valid_id(id)?;
-        let directory = self.item_dir(id);
-        if !directory.exists() {
-            return Err(AppError::ItemNotFound(id.to_string()));
+    /// Remove scratch files and directories left behind by interrupted writes.
+    ///
+    /// Metadata staged for content that was already renamed into place is moved into place as
+    /// well, so `meta.json` always describes `item.txt`. Item directories emptied by the cleanup
+    /// are removed too. Runs under the exclusive store lock so writes in progress in other
+    /// processes are never mistaken for orphans. Returns the number of entries cleaned up.
+    pub fn recover(&self) -> Result<usize, AppError> {
+        if !self.root_path.is_dir() {
+            return Ok(0);
         }
-        fs::remove_dir_all(directory)?;
-        Ok(())
+
+        let _store = self.lock_store(LockMode::Exclusive)?;
+        recover_dir(&self.root_path)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serial_test::serial;
-    use std::ffi::OsString;
-    use std::fs;
-    use std::path::PathBuf;
-    use tempfile::TempDir;
+    fn ensure_exists(&self, id: &ItemId) -> Result<PathBuf, AppError> {
+        let file = self.item_file(id);
+        if file.is_file() { Ok(file) } else { Err(AppError::ItemNotFound(id.to_string())) }
+    }
 
-    struct TestContext {
-        root: TempDir,
-        original_home: Option<OsString>,
+    fn lock_store(&self, mode: LockMode) -> Result<LockGuard, AppError> {
+        fs::create_dir_all(&self.root_path)?;
+        self.acquire(&self.root_path.join(STORE_LOCK_FILE_NAME), mode, "the store")
     }
 
-    impl TestContext {
-        fn new() -> Self {
-            let root = TempDir::new().expect("failed to create temp dir");
-            let original_home = std::env::var_os("HOME");
-            unsafe {
-                std::env::set_var("HOME", root.path());
+    fn item_lock_file(&self, id: &str) -> PathBuf {
+        let name = id.replace(SEPARATOR, &FLAT_SEPARATOR.to_string());
+        self.root_path.join(ITEM_LOCKS_DIR_NAME).join(format!("{name}.lock"))
+    }
+
+    fn lock_item(&self, id: &str, mode: LockMode) -> Result<LockGuard, AppError> {
+        let path = self.item_lock_file(id);
+        fs::create_dir_all(self.root_path.join(ITEM_LOCKS_DIR_NAME))?;
+        loop {
+            // A delete may remove the file while we wait for it; locking the fresh file it leaves
+            // room for is then the only way to be exclusive.
+            let guard = self.acquire(&path, mode, &format!("item '{id}'"))?;
+            if guard.holds(&path)? {
+                return Ok(guard);
             }
+        }
+    }
+
+    /// Remove the lock file of an item that no longer exists, so the lock directory does not
+    /// grow with every item ever written.
+    ///
+    /// The caller holds the item lock exclusively; processes waiting on the removed file notice
+    /// in [`Self::lock_item`] and lock a new one.
+    #[cfg(unix)]
+    fn remove_item_lock(&self, id: &ItemId) -> Result<(), AppError> {
+        Ok(fs::remove_file(self.item_lock_file(id))?)
+    }
+
+    /// Open files cannot be unlinked safely everywhere, so lock files are kept on this platform.
+    #[cfg(not(unix))]
+    fn remove_item_lock(&self, _: &ItemId) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Take an advisory lock on `path`, polling until [`Self::with_lock_timeout`] elapses.
+    fn acquire(&self, path: &Path, mode: LockMode, resource: &str) -> Result<LockGuard, AppError> {
+        acquire_lock(path, mode, self.lock_timeout, resource)
+    }
 
-            Self { root, original_home }
+    fn ensure_valid_tag(&self, tag: &str) -> Result<(), AppError> {
+        if is_valid_tag(tag) {
+            Ok(())
+        } else {
+            Err(AppError::config_error(format!("invalid tag: {tag}")))
         }
+    }
 
-        fn storage(&self) -> FilesystemStorage {
-            FilesystemStorage::new_default().expect("storage initialization should succeed")
+    /// Apply `change` to the tags of an existing item, persisting them only if it reports a change.
+    fn change_tags(
+        &self,
+        id: &ItemId,
+        tag: &str,
+        change: impl FnOnce(&mut BTreeSet<String>) -> bool,
+    ) -> Result<bool, AppError> {
+        self.ensure_valid_tag(tag)?;
+        let _locks = self.lock_for_write(id)?;
+        self.ensure_exists(id)?;
+        let mut metadata = self.read_metadata(id)?;
+        if !change(&mut metadata.tags) {
+            return Ok(false);
         }
+        self.write_metadata(id, &metadata)?;
+        Ok(true)
+    }
 
-        fn storage_root(&self) -> PathBuf {
-            self.root.path().join(".config").join("rs-cli-tmpl")
+    /// Lock a mutation of `id`: the store exclusively, then the item exclusively.
+    ///
+    /// On case-insensitive filesystems another spelling of an existing item would resolve to
+    /// that item's directory, so such writes are refused.
+    fn lock_for_write(&self, id: &ItemId) -> Result<(LockGuard, LockGuard), AppError> {
+        let store = self.lock_store(LockMode::Exclusive)?;
+        let item = self.lock_item(id, LockMode::Exclusive)?;
+        if self.is_case_insensitive() {
+            self.ensure_no_case_collision(id)?;
+        }
+        Ok((store, item))
+    }
+
+    /// Lock a read of an existing item, returning the content path alongside the shared lock.
+    fn lock_for_read(&self, id: &ItemId) -> Result<(PathBuf, LockGuard), AppError> {
+        self.ensure_exists(id)?;
+        let guard = self.lock_item(id, LockMode::Shared)?;
+        // The item may have been deleted while we waited.
+        Ok((self.ensure_exists(id)?, guard))
+    }
+
+    fn read_metadata(&self, id: &str) -> Result<ItemMetadata, AppError> {
+        match fs::read(self.metadata_file(id)) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).map_err(io::Error::from)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => self.legacy_metadata(id),
+            Err(err) => Err(err.into()),
         }
     }
 
-    impl Drop for TestContext {
-        fn drop(&mut self) {
-            match &self.original_home {
-                Some(value) => unsafe {
-                    std::env::set_var("HOME", value);
-                },
-                None => unsafe {
-                    std::env::remove_var("HOME");
-                },
+    /// Derive metadata for items written before the sidecar existed.
+    fn legacy_metadata(&self, id: &str) -> Result<ItemMetadata, AppError> {
+        let file = self.item_file(id);
+        let mut metadata = ItemMetadata::from_digest(digest_file(&file)?, None);
+        if let Ok(modified) = fs::metadata(&file)?.modified()
+            && let Ok(elapsed) = modified.duration_since(UNIX_EPOCH)
+        {
+            metadata.created_at = elapsed.as_secs();
+            metadata.updated_at = elapsed.as_secs();
+        }
+        Ok(metadata)
+    }
+
+    fn write_metadata(&self, id: &str, metadata: &ItemMetadata) -> Result<(), AppError> {
+        let staged = Staged::new(&self.item_dir(id));
+        write_metadata_file(&staged.path, metadata)?;
+        staged.commit(&self.metadata_file(id))
+    }
+
+    /// Replace the content of `id` from `reader`, archiving the current revision first, and its
+    /// tags with `tags` if given.
+    fn replace(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: Option<&[String]>,
+    ) -> Result<(), AppError> {
+        let _locks = self.lock_for_write(id)?;
+        let file = self.ensure_exists(id)?;
+        let current = self.read_metadata(id)?;
+        let mut metadata = current.clone();
+        let staged = Staged::new(&self.item_dir(id));
+        metadata.touch_digest(write_stream(&staged.path, reader)?);
+        let reindex = self.reindex(id, Some(&file), Some(&staged.path))?;
+        if let Some(content_type) = content_type {
+            metadata.content_type = Some(content_type.to_string());
+        }
+        if let Some(tags) = tags {
+            metadata.tags = tags.iter().cloned().collect();
+        }
+        self.archive_current(id, &current)?;
+        self.commit_revision(id, staged, &metadata)?;
+        reindex.finish()
+    }
+
+    /// Move the staged content of `id` and the `metadata` describing it into place.
+    ///
+    /// The metadata is staged next to the content before either is renamed, so a crash between
+    /// the two renames is finished by [`Self::recover`] instead of leaving stale metadata.
+    fn commit_revision(
+        &self,
+        id: &str,
+        content: Staged,
+        metadata: &ItemMetadata,
+    ) -> Result<(), AppError> {
+        let staged_metadata = content.companion(STAGED_METADATA_SUFFIX);
+        write_metadata_file(&staged_metadata.path, metadata)?;
+        content.commit(&self.item_file(id))?;
+        // Recovery rolls the staged metadata forward from here on, so it is kept even on failure.
+        let staged_metadata = staged_metadata.keep();
+        fs::rename(&staged_metadata, self.metadata_file(id))?;
+        sync_dir(&self.item_dir(id))?;
+        Ok(())
+    }
+
+    /// Keep the current revision of `id`, described by `current`, before it is replaced.
+    fn archive_current(&self, id: &str, current: &ItemMetadata) -> Result<(), AppError> {
+        if self.history_limit == 0 {
+            return self.prune_versions(id);
+        }
+
+        fs::create_dir_all(self.versions_dir(id))?;
+        let archived = self.version_file(id, current.version, "txt");
+        // A crash between archiving and replacing the content can leave a stale copy behind.
+        if let Err(err) = fs::remove_file(&archived)
+            && err.kind() != io::ErrorKind::NotFound
+        {
+            return Err(err.into());
+        }
+        // Content files are only ever replaced by renames, so a hard link is a stable snapshot.
+        if fs::hard_link(self.item_file(id), &archived).is_err() {
+            let staged = Staged::new(&self.item_dir(id));
+            fs::copy(self.item_file(id), &staged.path)?;
+            staged.commit(&archived)?;
+        }
+
+        let staged = Staged::new(&self.item_dir(id));
+        write_metadata_file(&staged.path, current)?;
+        staged.commit(&self.version_file(id, current.version, "json"))?;
+        self.prune_versions(id)
+    }
+
+    /// Drop the oldest archived revisions beyond the history limit.
+    fn prune_versions(&self, id: &str) -> Result<(), AppError> {
+        let archived = self.archived_versions(id)?;
+        let excess = archived.len().saturating_sub(self.history_limit);
+        for version in &archived[..excess] {
+            fs::remove_file(self.version_file(id, *version, "json"))?;
+            let _ = fs::remove_file(self.version_file(id, *version, "txt"));
+        }
+        Ok(())
+    }
+
+    /// Numbers of the archived revisions of `id`, oldest first.
+    fn archived_versions(&self, id: &str) -> Result<Vec<u64>, AppError> {
+        let entries = match fs::read_dir(self.versions_dir(id)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut versions = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "json")
+                && let Some(version) =
+                    path.file_stem().and_then(|stem| stem.to_str()?.parse::<u64>().ok())
+            {
+                versions.push(version);
             }
         }
+        versions.sort_unstable();
+        Ok(versions)
     }
+}
 
-    #[test]
-    #[serial]
-    fn add_item_persists_contents() {
-        let ctx = TestContext::new();
-        let storage = ctx.storage();
+/// Scratch file or directory that is deleted on drop unless committed into place.
+pub(crate) struct Staged {
+    pub(crate) path: PathBuf,
+    committed: bool,
+}
+
+impl Staged {
+    fn new(parent: &Path) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        let sequence = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let name = format!("{TEMP_PREFIX}{}-{nanos}-{sequence}", process::id());
+        Self { path: parent.join(name), committed: false }
+    }
 
-        storage.add_item("demo", "example content").expect("add_item should succeed");
+    /// Entry staged at a fixed `path` rather than under a fresh scratch name.
+    pub(crate) fn at(path: PathBuf) -> Self {
+        Self { path, committed: false }
+    }
 
-        let saved = ctx.storage_root().join("demo").join("item.txt");
-        let content = fs::read_to_string(saved).expect("failed to read saved item");
-        assert_eq!(content, "example content");
+    /// Entry staged next to this one, named after it with `suffix` appended.
+    fn companion(&self, suffix: &str) -> Self {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(suffix);
+        Self { path: PathBuf::from(name), committed: false }
     }
 
-    #[test]
-    #[serial]
-    fn list_items_returns_all_ids() {
-        let ctx = TestContext::new();
-        let storage = ctx.storage();
+    /// Stop deleting the staged entry on drop, returning its path.
+    fn keep(mut self) -> PathBuf {
+        self.committed = true;
+        std::mem::take(&mut self.path)
+    }
 
-        storage.add_item("first", "one").unwrap();
-        storage.add_item("second", "two").unwrap();
+    /// Atomically rename the staged entry to `target` and make the rename durable.
+    pub(crate) fn commit(mut self, target: &Path) -> Result<(), AppError> {
+        fs::rename(&self.path, target)?;
+        self.committed = true;
+        if let Some(parent) = target.parent() {
+            sync_dir(parent)?;
+        }
+        Ok(())
+    }
+}
 
-        let mut items = storage.list_items().expect("list_items succeeds");
-        items.sort();
-        assert_eq!(items, vec!["first", "second"]);
+impl Drop for Staged {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = remove_entry(&self.path);
+        }
     }
+}
 
-    #[test]
-    #[serial]
-    fn delete_item_removes_directory() {
-        let ctx = TestContext::new();
-        let storage = ctx.storage();
+/// Add the items below `directory`, whose ids start with `prefix`, to `ids`.
+///
+/// Directories no identifier could name, such as ones created by hand, are skipped.
+fn collect_ids(directory: &Path, prefix: &str, ids: &mut Vec<ItemId>) -> Result<(), AppError> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if name.starts_with('.') || !path.is_dir() {
+            continue;
+        }
 
-        storage.add_item("temp", "data").unwrap();
-        storage.delete_item("temp").expect("delete succeeds");
+        let id = format!("{prefix}{name}");
+        if path.join(ITEM_FILE_NAME).is_file() {
+            ids.extend(ItemId::from_stored(&id));
+        } else {
+            collect_ids(&path, &format!("{id}{SEPARATOR}"), ids)?;
+        }
+    }
+    Ok(())
+}
 
-        assert!(!ctx.storage_root().join("temp").exists());
+/// Clean up the scratch entries in `directory` and, recursively, in the item and namespace
+/// directories below it, returning how many were handled.
+///
+/// Directories emptied by the cleanup are removed as well.
+fn recover_dir(directory: &Path) -> Result<usize, AppError> {
+    let entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<BTreeSet<_>>>()?;
+    let is_item = entries.contains(&directory.join(ITEM_FILE_NAME));
+    let mut removed = 0;
+    for path in &entries {
+        if is_temp(path) {
+            let content = path
+                .to_str()
+                .and_then(|path| path.strip_suffix(STAGED_METADATA_SUFFIX))
+                .map(PathBuf::from);
+            if is_item && content.is_some_and(|content| !entries.contains(&content)) {
+                // Its content was committed before the interruption; finish the replacement.
+                fs::rename(path, directory.join(METADATA_FILE_NAME))?;
+                sync_dir(directory)?;
+            } else {
+                remove_entry(path)?;
+            }
+            removed += 1;
+        } else if path.is_dir() && !is_hidden(path) {
+            let orphans = recover_dir(path)?;
+            if orphans > 0 && fs::read_dir(path)?.next().is_none() {
+                fs::remove_dir(path)?;
+            }
+            removed += orphans;
+        }
+    }
+    Ok(removed)
+}
+
+/// Path of `id` below `root` the way a case-insensitive filesystem resolves it: each segment
+/// names the existing entry spelled the same ignoring case, or is kept as written.
+fn resolve_ignoring_case(root: &Path, id: &str) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for segment in id.split(SEPARATOR) {
+        let folded = segment.to_lowercase();
+        let existing = fs::read_dir(&path).ok().and_then(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name())
+                .find(|name| name.to_str().is_some_and(|name| name.to_lowercase() == folded))
+        });
+        path.push(existing.unwrap_or_else(|| segment.into()));
+    }
+    path
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.'))
+}
+
+fn is_temp(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(TEMP_PREFIX))
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) }
+}
+
+/// Take an advisory lock on `path`, creating the file if needed and polling until `timeout`
+/// elapses.
+pub(crate) fn acquire_lock(
+    path: &Path,
+    mode: LockMode,
+    timeout: Duration,
+    resource: &str,
+) -> Result<LockGuard, AppError> {
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_millis(1);
+    loop {
+        let attempt = match mode {
+            LockMode::Shared => file.try_lock_shared(),
+            LockMode::Exclusive => file.try_lock(),
+        };
+        match attempt {
+            Ok(()) => return Ok(LockGuard { file }),
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(AppError::LockTimeout(resource.to_string()));
+        }
+        thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(Duration::from_millis(50));
+    }
+}
+
+/// Flush a directory entry to disk so a preceding rename survives a crash.
+#[cfg(unix)]
+pub(crate) fn sync_dir(path: &Path) -> io::Result<()> {
+    File::open(path)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing on this platform; renames are durable enough.
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Stream `reader` into a new file at `path`, digesting it on the way and syncing it to disk.
+fn write_stream(path: &Path, reader: &mut dyn Read) -> Result<ContentDigest, AppError> {
+    let file = File::create_new(path)?;
+    let mut writer = DigestWriter::new(BufWriter::with_capacity(WRITE_BUFFER_SIZE, file));
+    io::copy(reader, &mut writer)?;
+    let (writer, digest) = writer.finish()?;
+    writer.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+    Ok(digest)
+}
+
+fn write_metadata_file(path: &Path, metadata: &ItemMetadata) -> Result<(), AppError> {
+    let bytes = serde_json::to_vec_pretty(metadata).map_err(io::Error::from)?;
+    let mut file = File::create_new(path)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Digest a file's content without loading it into memory.
+fn digest_file(path: &Path) -> Result<ContentDigest, AppError> {
+    let mut writer = DigestWriter::new(io::sink());
+    io::copy(&mut File::open(path)?, &mut writer)?;
+    let (_, digest) = writer.finish()?;
+    Ok(digest)
+}
+
+fn resolve_root(lookup: impl Fn(&str) -> Option<OsString>) -> Result<PathBuf, AppError> {
+    let var = |name: &str| lookup(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    if let Some(root) = var(STORE_ENV_VAR) {
+        return Ok(root);
+    }
+    let legacy = var("HOME").map(|home| home.join(".config").join(APP_DIR_NAME));
+    let xdg = var("XDG_DATA_HOME").or_else(|| var("XDG_CONFIG_HOME"));
+    match (xdg.map(|home| home.join(APP_DIR_NAME)), legacy) {
+        (Some(root), Some(legacy)) if !holds_store(&root) && holds_store(&legacy) => Ok(legacy),
+        (Some(root), _) => Ok(root),
+        (None, legacy) => {
+            legacy.ok_or_else(|| AppError::config_error("HOME environment variable not set"))
+        }
+    }
+}
+
+/// Whether `dir` holds anything besides the config file that may share it.
+fn holds_store(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| {
+        entries.any(|entry| entry.is_ok_and(|entry| entry.file_name() != CONFIG_FILE_NAME))
+    })
+}
+
+impl Storage for FilesystemStorage {
+    fn id_rules(&self) -> IdRules {
+        self.id_rules
+    }
+
+    fn add_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.add_item_from_reader(id, &mut &content[..], content_type)
+    }
+
+    fn update_item(
+        &self,
+        id: &ItemId,
+        content: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.update_item_from_reader(id, &mut &content[..], content_type)
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        let _locks = self.lock_for_write(id)?;
+        let file = self.ensure_exists(id)?;
+        let current = self.read_metadata(id)?;
+        let mut metadata = current.clone();
+        let staged = Staged::new(&self.item_dir(id));
+        let mut combined = File::open(&file)?.chain(content);
+        metadata.touch_digest(write_stream(&staged.path, &mut combined)?);
+        let reindex = self.reindex(id, Some(&file), Some(&staged.path))?;
+        self.archive_current(id, &current)?;
+        self.commit_revision(id, staged, &metadata)?;
+        reindex.finish()
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        let (file, _lock) = self.lock_for_read(id)?;
+        Ok(fs::read(file)?)
+    }
+
+    fn add_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.add_tagged_item_from_reader(id, reader, content_type, &[])
+    }
+
+    fn add_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        let _locks = self.lock_for_write(id)?;
+        self.ensure_placeable(id)?;
+
+        // Build the whole item in a scratch directory so it appears complete or not at all.
+        fs::create_dir_all(&self.root_path)?;
+        let staged = Staged::new(&self.root_path);
+        fs::create_dir(&staged.path)?;
+        let digest = write_stream(&staged.path.join(ITEM_FILE_NAME), reader)?;
+        let mut metadata = ItemMetadata::from_digest(digest, content_type);
+        metadata.tags.extend(tags.iter().cloned());
+        write_metadata_file(&staged.path.join(METADATA_FILE_NAME), &metadata)?;
+        sync_dir(&staged.path)?;
+        let reindex = self.reindex(id, None, Some(&staged.path.join(ITEM_FILE_NAME)))?;
+        staged.commit(&self.prepare_item_dir(id)?)?;
+        reindex.finish()
+    }
+
+    fn update_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.replace(id, reader, content_type, None)
+    }
+
+    fn update_tagged_item_from_reader(
+        &self,
+        id: &ItemId,
+        reader: &mut dyn Read,
+        content_type: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), AppError> {
+        validate_tags(tags.iter().map(String::as_str))?;
+        self.replace(id, reader, content_type, Some(tags))
+    }
+
+    fn read_item_into(&self, id: &ItemId, writer: &mut dyn Write) -> Result<u64, AppError> {
+        let (file, _lock) = self.lock_for_read(id)?;
+        Ok(io::copy(&mut File::open(file)?, writer)?)
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        let (_, _lock) = self.lock_for_read(id)?;
+        self.read_metadata(id)
+    }
+
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |tags| tags.insert(tag.to_string()))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.change_tags(id, tag, |tags| tags.remove(tag))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        let (_, _lock) = self.lock_for_read(id)?;
+        let current = self.read_metadata(id)?;
+
+        let mut versions = Vec::new();
+        for version in self.archived_versions(id)? {
+            if version >= current.version {
+                continue;
+            }
+            let bytes = fs::read(self.version_file(id, version, "json"))?;
+            let mut metadata: ItemMetadata =
+                serde_json::from_slice(&bytes).map_err(io::Error::from)?;
+            metadata.version = version;
+            versions.push(metadata);
+        }
+        versions.push(current);
+        Ok(versions)
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        let (file, _lock) = self.lock_for_read(id)?;
+        let current = self.read_metadata(id)?;
+
+        let source = if version == current.version {
+            file
+        } else {
+            let archived = self.version_file(id, version, "txt");
+            if version > current.version || !archived.is_file() {
+                return Err(AppError::VersionNotFound(id.to_string(), version));
+            }
+            archived
+        };
+        Ok(io::copy(&mut File::open(source)?, writer)?)
+    }
+
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        if !self.root_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let _store = self.lock_store(LockMode::Shared)?;
+        self.item_ids()
+    }
+
+    fn search_candidates(&self, query: &str) -> Result<Option<Vec<ItemId>>, AppError> {
+        let index = self.search_index();
+        let terms = terms_of(query);
+        if terms.is_empty() || !index.exists() {
+            return Ok(None);
+        }
+
+        let _store = self.lock_store(LockMode::Shared)?;
+        Ok(Some(index.lookup(&terms)?.iter().filter_map(|id| ItemId::from_stored(id)).collect()))
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let _locks = self.lock_for_write(id)?;
+        let file = self.ensure_exists(id)?;
+        let directory = self.item_dir(id);
+
+        let reindex = self.reindex(id, Some(&file), None)?;
+        let trash = self.trash_dir();
+        fs::create_dir_all(&trash)?;
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+        let name = id.replace(SEPARATOR, &FLAT_SEPARATOR.to_string());
+        fs::rename(&directory, trash.join(format!("{nanos}-{name}")))?;
+        sync_dir(&trash)?;
+        if let Some(parent) = directory.parent() {
+            sync_dir(parent)?;
+        }
+        self.prune_namespaces(id);
+        self.remove_item_lock(id)?;
+        reindex.finish()
+    }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let _locks = self.lock_for_write(id)?;
+        let file = self.ensure_exists(id)?;
+        let reindex = self.reindex(id, Some(&file), None)?;
+        self.discard(&self.item_dir(id))?;
+        self.prune_namespaces(id);
+        self.remove_item_lock(id)?;
+        reindex.finish()
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        if !self.trash_dir().is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let _store = self.lock_store(LockMode::Shared)?;
+        Ok(self.trash_entries()?.into_iter().map(|(entry, _)| entry).collect())
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        let _locks = self.lock_for_write(id)?;
+        self.ensure_placeable(id)?;
+
+        let (_, path) = self
+            .trash_entries()?
+            .into_iter()
+            .rfind(|(entry, _)| entry.id == id.as_str())
+            .ok_or_else(|| AppError::ItemNotFound(id.to_string()))?;
+        let reindex = self.reindex(id, None, Some(&path.join(ITEM_FILE_NAME)))?;
+        let directory = self.prepare_item_dir(id)?;
+        fs::rename(&path, &directory)?;
+        if let Some(parent) = directory.parent() {
+            sync_dir(parent)?;
+        }
+        sync_dir(&self.trash_dir())?;
+        reindex.finish()
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        if !self.trash_dir().is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let _store = self.lock_store(LockMode::Exclusive)?;
+        let cutoff = older_than.map(|age| unix_now().saturating_sub(age.as_secs()));
+        let mut removed = Vec::new();
+        for (entry, path) in self.trash_entries()? {
+            if cutoff.is_none_or(|cutoff| entry.deleted_at <= cutoff) {
+                self.discard(&path)?;
+                removed.push(entry);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::id;
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    struct TestContext {
+        root: TempDir,
+    }
+
+    impl TestContext {
+        fn new() -> Self {
+            Self { root: TempDir::new().expect("failed to create temp dir") }
+        }
+
+        fn storage(&self) -> FilesystemStorage {
+            FilesystemStorage::new(self.storage_root())
+        }
+
+        fn storage_root(&self) -> PathBuf {
+            self.root.path().join(".config").join("rs-cli-tmpl")
+        }
+    }
+
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.into())
+    }
+
+    #[test]
+    fn resolve_root_prefers_explicit_override() {
+        let vars = [
+            (STORE_ENV_VAR, "/custom/store"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("HOME", "/home/user"),
+        ];
+        assert_eq!(resolve_root(lookup(&vars)).unwrap(), PathBuf::from("/custom/store"));
+    }
+
+    #[test]
+    fn resolve_root_uses_xdg_directories_before_home() {
+        let data = [("XDG_DATA_HOME", "/xdg/data"), ("XDG_CONFIG_HOME", "/xdg/config")];
+        assert_eq!(resolve_root(lookup(&data)).unwrap(), PathBuf::from("/xdg/data/rs-cli-tmpl"));
+
+        let config = [("XDG_DATA_HOME", ""), ("XDG_CONFIG_HOME", "/xdg/config")];
+        assert_eq!(
+            resolve_root(lookup(&config)).unwrap(),
+            PathBuf::from("/xdg/config/rs-cli-tmpl")
+        );
+    }
+
+    #[test]
+    fn resolve_root_falls_back_to_home_config() {
+        let vars = [("HOME", "/home/user")];
+        assert_eq!(
+            resolve_root(lookup(&vars)).unwrap(),
+            PathBuf::from("/home/user/.config/rs-cli-tmpl")
+        );
+
+        let result = resolve_root(lookup(&[]));
+        assert!(matches!(result, Err(AppError::ConfigError(_))));
+    }
+
+    #[test]
+    fn resolve_root_keeps_a_legacy_store_until_the_xdg_location_holds_one() {
+        let dir = TempDir::new().unwrap();
+        let (home, data) = (dir.path().join("home"), dir.path().join("data"));
+        let legacy = home.join(".config").join(APP_DIR_NAME);
+        let xdg = data.join(APP_DIR_NAME);
+        let vars = [("HOME", home.to_str().unwrap()), ("XDG_DATA_HOME", data.to_str().unwrap())];
+
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join(CONFIG_FILE_NAME), "").unwrap();
+        assert_eq!(resolve_root(lookup(&vars)).unwrap(), xdg);
+
+        FilesystemStorage::new(&legacy).add_item(&id("old"), b"kept", None).unwrap();
+        assert_eq!(resolve_root(lookup(&vars)).unwrap(), legacy);
+
+        FilesystemStorage::new(&xdg).add_item(&id("new"), b"moved", None).unwrap();
+        assert_eq!(resolve_root(lookup(&vars)).unwrap(), xdg);
+    }
+
+    #[test]
+    fn add_item_persists_contents() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"example content", None).expect("add_item should succeed");
+
+        let saved = ctx.storage_root().join("demo").join("item.txt");
+        let content = fs::read_to_string(saved).expect("failed to read saved item");
+        assert_eq!(content, "example content");
+    }
+
+    #[test]
+    fn add_item_fails_if_already_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"original", None).unwrap();
+        let result = storage.add_item(&id("demo"), b"replacement", None);
+
+        assert!(matches!(result, Err(AppError::ItemAlreadyExists(ref id)) if id == "demo"));
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"original");
+    }
+
+    #[test]
+    fn update_item_replaces_contents() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"original", None).unwrap();
+        storage.update_item(&id("demo"), b"replacement", None).expect("update_item should succeed");
+
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"replacement");
+    }
+
+    #[test]
+    fn update_item_fails_if_not_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.update_item(&id("missing"), b"value", None);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+        assert!(!ctx.storage_root().join("missing").exists());
+    }
+
+    #[test]
+    fn append_item_extends_contents() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("log"), b"one\n", None).unwrap();
+        storage.append_item(&id("log"), b"two\n").expect("append_item should succeed");
+
+        assert_eq!(storage.get_item(&id("log")).unwrap(), b"one\ntwo\n");
+    }
+
+    #[test]
+    fn append_item_fails_if_not_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.append_item(&id("missing"), b"value");
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    #[test]
+    fn get_item_returns_saved_contents() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"example content", None).unwrap();
+
+        let content = storage.get_item(&id("demo")).expect("get_item should succeed");
+        assert_eq!(content, b"example content");
+    }
+
+    #[test]
+    fn binary_content_round_trips() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        let payload = [0u8, 159, 146, 150, 255, b'\n', 0];
+
+        storage.add_item(&id("blob"), &payload, Some("application/octet-stream")).unwrap();
+        storage.append_item(&id("blob"), &[0xfe, 0xff]).unwrap();
+
+        let content = storage.get_item(&id("blob")).expect("get_item should succeed");
+        assert_eq!(content, [&payload[..], &[0xfe, 0xff]].concat());
+        assert_eq!(storage.item_metadata(&id("blob")).unwrap().size, 9);
+    }
+
+    #[test]
+    fn streamed_content_is_copied_without_buffering() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        let mut reader = io::repeat(b'x').take(1024 * 1024);
+
+        storage.add_item_from_reader(&id("large"), &mut reader, None).expect("add should succeed");
+
+        let mut copy = Vec::new();
+        let written = storage.read_item_into(&id("large"), &mut copy).expect("read should succeed");
+        assert_eq!(written, 1024 * 1024);
+        assert!(copy.iter().all(|byte| *byte == b'x'));
+
+        let metadata = storage.item_metadata(&id("large")).unwrap();
+        assert_eq!(metadata.size, 1024 * 1024);
+        assert_eq!(metadata.sha256, ContentDigest::of(&copy).sha256);
+    }
+
+    #[test]
+    fn update_item_from_reader_fails_if_not_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.update_item_from_reader(&id("missing"), &mut &b"data"[..], None);
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    /// Reader that fails partway through, standing in for a write interrupted mid-stream.
+    struct InterruptedReader {
+        remaining: usize,
+    }
+
+    impl Read for InterruptedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "simulated crash"));
+            }
+            let len = buf.len().min(self.remaining);
+            buf[..len].fill(b'x');
+            self.remaining -= len;
+            Ok(len)
+        }
+    }
+
+    fn stray_entries(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with(TEMP_PREFIX))
+            .collect()
+    }
+
+    #[test]
+    fn interrupted_add_leaves_no_partial_item() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let mut reader = InterruptedReader { remaining: 100_000 };
+        let result = storage.add_item_from_reader(&id("partial"), &mut reader, None);
+
+        assert!(result.is_err());
+        assert!(!ctx.storage_root().join("partial").exists());
+        assert!(stray_entries(&ctx.storage_root()).is_empty());
+        assert!(storage.list_items().unwrap().is_empty());
+    }
+
+    #[test]
+    fn interrupted_update_keeps_previous_content() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("demo"), b"original", None).unwrap();
+
+        let mut reader = InterruptedReader { remaining: 100_000 };
+        let result = storage.update_item_from_reader(&id("demo"), &mut reader, None);
+
+        assert!(result.is_err());
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"original");
+        assert_eq!(storage.item_metadata(&id("demo")).unwrap().size, 8);
+        assert!(stray_entries(&ctx.storage_root().join("demo")).is_empty());
+    }
+
+    #[test]
+    fn recover_removes_orphans_left_by_crashed_writes() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("demo"), b"kept", None).unwrap();
+
+        // What a process killed mid-write leaves behind: a staged item directory, a staged
+        // content file inside an item, and an item directory holding only a scratch file.
+        let root = ctx.storage_root();
+        fs::create_dir(root.join(".tmp-1-2-0")).unwrap();
+        fs::write(root.join(".tmp-1-2-0").join("item.txt"), "half").unwrap();
+        fs::write(root.join("demo").join(".tmp-1-3-0"), "half").unwrap();
+        fs::create_dir(root.join("stale")).unwrap();
+        fs::write(root.join("stale").join(".tmp-1-4-0"), "half").unwrap();
+        storage.add_item(&id("team/nested"), b"kept", None).unwrap();
+        fs::write(root.join("team/nested").join(".tmp-1-5-0"), "half").unwrap();
+
+        assert_eq!(storage.list_items().unwrap(), vec!["demo", "team/nested"]);
+        assert_eq!(storage.recover().unwrap(), 4);
+
+        assert!(stray_entries(&root).is_empty());
+        assert!(stray_entries(&root.join("demo")).is_empty());
+        assert!(stray_entries(&root.join("team/nested")).is_empty());
+        assert!(!root.join("stale").exists());
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"kept");
+        assert_eq!(storage.recover().unwrap(), 0);
+    }
+
+    #[test]
+    fn recover_finishes_metadata_staged_for_committed_content() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("done"), b"old", None).unwrap();
+        storage.add_item(&id("undone"), b"old", None).unwrap();
+        let mut replaced = storage.item_metadata(&id("done")).unwrap();
+        replaced.touch_digest(ContentDigest::of(b"new content"));
+        let staged_metadata = serde_json::to_vec(&replaced).unwrap();
+
+        // Interrupted between renaming the content and its metadata into place.
+        let root = ctx.storage_root();
+        fs::write(root.join("done").join(ITEM_FILE_NAME), "new content").unwrap();
+        fs::write(root.join("done").join(".tmp-1-2-0.json"), &staged_metadata).unwrap();
+        // Interrupted before either rename: the old revision stays.
+        fs::write(root.join("undone").join(".tmp-1-3-0"), "new content").unwrap();
+        fs::write(root.join("undone").join(".tmp-1-3-0.json"), &staged_metadata).unwrap();
+
+        assert_eq!(storage.recover().unwrap(), 3);
+        assert_eq!(storage.item_metadata(&id("done")).unwrap(), replaced);
+        assert_eq!(storage.item_metadata(&id("undone")).unwrap().size, 3);
+        assert_eq!(storage.get_item(&id("undone")).unwrap(), b"old");
+        assert!(stray_entries(&root.join("done")).is_empty());
+        assert!(stray_entries(&root.join("undone")).is_empty());
+    }
+
+    #[test]
+    fn writes_leave_no_scratch_files_behind() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"one", None).unwrap();
+        storage.update_item(&id("demo"), b"two", None).unwrap();
+        storage.append_item(&id("demo"), b"!").unwrap();
+        storage.add_item(&id("gone"), b"x", None).unwrap();
+        storage.delete_item(&id("gone")).unwrap();
+
+        assert!(stray_entries(&ctx.storage_root()).is_empty());
+        assert!(stray_entries(&ctx.storage_root().join("demo")).is_empty());
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"two!");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn deleting_items_removes_their_lock_files() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        let locks = ctx.storage_root().join(ITEM_LOCKS_DIR_NAME);
+        for name in ["team/a/one", "team/two", "solo"] {
+            storage.add_item(&id(name), b"x", None).unwrap();
+        }
+
+        storage.delete_item(&id("team/a/one")).unwrap();
+        storage.purge_item(&id("solo")).unwrap();
+
+        assert!(!locks.join("team%a%one.lock").exists());
+        assert!(!locks.join("solo.lock").exists());
+        assert!(locks.join("team%two.lock").is_file());
+        storage.restore_item(&id("team/a/one")).unwrap();
+        assert!(locks.join("team%a%one.lock").is_file());
+    }
+
+    #[test]
+    fn lock_files_of_ids_ending_in_lock_do_not_collide() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("a.lock/b"), b"x", None).unwrap();
+        storage.add_item(&id("a"), b"y", None).unwrap();
+        storage.add_item(&id("c"), b"z", None).unwrap();
+        storage.add_item(&id("c.lock/d"), b"w", None).unwrap();
+
+        for (name, content) in [("a.lock/b", "x"), ("a", "y"), ("c", "z"), ("c.lock/d", "w")] {
+            assert_eq!(storage.get_item(&id(name)).unwrap(), content.as_bytes());
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn locks_on_a_removed_lock_file_are_retaken_on_the_new_one() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage().with_lock_timeout(Duration::from_millis(50));
+        let path = storage.item_lock_file("demo");
+        let stale = storage.lock_item("demo", LockMode::Shared).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert!(!stale.holds(&path).unwrap());
+        let fresh = storage.lock_item("demo", LockMode::Exclusive).unwrap();
+        assert!(fresh.holds(&path).unwrap());
+    }
+
+    #[test]
+    fn held_item_lock_times_out_readers_and_writers() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage().with_lock_timeout(Duration::from_millis(50));
+        storage.add_item(&id("demo"), b"value", None).unwrap();
+
+        let writer = storage.lock_item(&id("demo"), LockMode::Exclusive).unwrap();
+        let result = storage.get_item(&id("demo"));
+        assert!(matches!(result, Err(AppError::LockTimeout(ref what)) if what == "item 'demo'"));
+        drop(writer);
+
+        let reader = storage.lock_item(&id("demo"), LockMode::Shared).unwrap();
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"value");
+        let result = storage.update_item(&id("demo"), b"blocked", None);
+        assert!(matches!(result, Err(AppError::LockTimeout(_))));
+        drop(reader);
+
+        storage.update_item(&id("demo"), b"free", None).unwrap();
+        assert_eq!(storage.get_item(&id("demo")).unwrap(), b"free");
+    }
+
+    #[test]
+    fn updates_keep_prior_versions() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"one", Some("text/plain")).unwrap();
+        storage.update_item(&id("demo"), b"two", None).unwrap();
+        storage.append_item(&id("demo"), b"!").unwrap();
+
+        let versions = storage.list_versions(&id("demo")).expect("list_versions should succeed");
+        let numbers: Vec<u64> = versions.iter().map(|metadata| metadata.version).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(versions[0].sha256, ContentDigest::of(b"one").sha256);
+        assert_eq!(versions[2].size, 4);
+
+        for (version, expected) in [(1, &b"one"[..]), (2, b"two"), (3, b"two!")] {
+            let mut content = Vec::new();
+            storage.read_version_into(&id("demo"), version, &mut content).unwrap();
+            assert_eq!(content, expected);
+        }
+        let result = storage.read_version_into(&id("demo"), 4, &mut Vec::new());
+        assert!(matches!(result, Err(AppError::VersionNotFound(ref id, 4)) if id == "demo"));
+    }
+
+    #[test]
+    fn history_limit_prunes_oldest_versions() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage().with_history_limit(2);
+
+        storage.add_item(&id("demo"), b"v1", None).unwrap();
+        for content in [b"v2", b"v3", b"v4"] {
+            storage.update_item(&id("demo"), content, None).unwrap();
+        }
+
+        let numbers: Vec<u64> =
+            storage.list_versions(&id("demo")).unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(numbers, vec![2, 3, 4]);
+        let result = storage.read_version_into(&id("demo"), 1, &mut Vec::new());
+        assert!(matches!(result, Err(AppError::VersionNotFound(_, 1))));
+
+        let storage = storage.with_history_limit(0);
+        storage.update_item(&id("demo"), b"v5", None).unwrap();
+        let numbers: Vec<u64> =
+            storage.list_versions(&id("demo")).unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(numbers, vec![5]);
+        assert!(!ctx.storage_root().join("demo").join("versions").join("4.txt").exists());
+    }
+
+    #[test]
+    fn get_item_fails_if_not_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.get_item(&id("missing"));
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    #[test]
+    fn add_item_writes_metadata_sidecar() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"hello", Some("text/plain")).unwrap();
+
+        assert!(ctx.storage_root().join("demo").join("meta.json").is_file());
+        let metadata = storage.item_metadata(&id("demo")).expect("item_metadata should succeed");
+        assert_eq!(metadata.size, 5);
+        assert_eq!(metadata.sha256, ContentDigest::of(b"hello").sha256);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+    }
+
+    #[test]
+    fn update_and_append_refresh_metadata() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("demo"), b"one", Some("text/plain")).unwrap();
+        let created = storage.item_metadata(&id("demo")).unwrap();
+
+        storage.update_item(&id("demo"), b"three", None).unwrap();
+        storage.append_item(&id("demo"), b"!").unwrap();
+
+        let metadata = storage.item_metadata(&id("demo")).unwrap();
+        assert_eq!(metadata.created_at, created.created_at);
+        assert!(metadata.updated_at >= created.updated_at);
+        assert_eq!(metadata.size, 6);
+        assert_eq!(metadata.sha256, ContentDigest::of(b"three!").sha256);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+    }
+
+    #[test]
+    fn item_metadata_falls_back_for_items_without_sidecar() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let directory = ctx.storage_root().join("legacy");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("item.txt"), "old").unwrap();
+
+        let metadata = storage.item_metadata(&id("legacy")).expect("item_metadata should succeed");
+        assert_eq!(metadata.size, 3);
+        assert_eq!(metadata.content_type, None);
+    }
+
+    #[test]
+    fn item_metadata_fails_if_not_exists() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.item_metadata(&id("missing"));
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    #[test]
+    fn tags_persist_across_content_changes() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("demo"), b"one", None).unwrap();
+
+        assert!(storage.add_tag(&id("demo"), "work").unwrap());
+        assert!(!storage.add_tag(&id("demo"), "work").unwrap());
+        assert!(storage.add_tag(&id("demo"), "urgent").unwrap());
+        storage.update_item(&id("demo"), b"two", None).unwrap();
+
+        let tags = storage.item_metadata(&id("demo")).unwrap().tags;
+        assert_eq!(tags.into_iter().collect::<Vec<_>>(), ["urgent", "work"]);
+
+        assert!(storage.remove_tag(&id("demo"), "urgent").unwrap());
+        assert!(!storage.remove_tag(&id("demo"), "urgent").unwrap());
+        let tags = storage.item_metadata(&id("demo")).unwrap().tags;
+        assert_eq!(tags.into_iter().collect::<Vec<_>>(), ["work"]);
+    }
+
+    #[test]
+    fn tag_changes_validate_tag_and_item() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("demo"), b"one", None).unwrap();
+
+        let result = storage.add_tag(&id("demo"), "two words");
+        assert!(
+            matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("tag"))
+        );
+        let result = storage.add_tag(&id("missing"), "work");
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    fn candidates(storage: &FilesystemStorage, query: &str) -> Option<Vec<ItemId>> {
+        storage.search_candidates(query).unwrap()
+    }
+
+    #[test]
+    fn search_index_follows_every_write() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        storage.add_item(&id("groceries"), b"Buy milk", None).unwrap();
+        assert_eq!(candidates(&storage, "milk"), None, "no index until it is built");
+
+        let report = storage.rebuild_index().unwrap();
+        assert_eq!((report.items, report.terms), (1, 3));
+        assert_eq!(candidates(&storage, "MILK").unwrap(), ["groceries"]);
+
+        storage.add_item(&id("shake"), b"milkshake", None).unwrap();
+        storage.append_item(&id("groceries"), b" and bread").unwrap();
+        storage.update_item(&id("shake"), b"smoothie", None).unwrap();
+        assert_eq!(candidates(&storage, "milk").unwrap(), ["groceries"]);
+        assert_eq!(candidates(&storage, "bread").unwrap(), ["groceries"]);
+
+        storage.delete_item(&id("groceries")).unwrap();
+        assert!(candidates(&storage, "milk").unwrap().is_empty());
+        storage.restore_item(&id("groceries")).unwrap();
+        assert_eq!(candidates(&storage, "milk").unwrap(), ["groceries"]);
+        storage.purge_item(&id("shake")).unwrap();
+        assert!(candidates(&storage, "smoothie").unwrap().is_empty());
+
+        assert_eq!(candidates(&storage, "mi"), None, "too short to narrow down");
+        let report = storage.verify_index().unwrap();
+        assert_eq!((report.items, report.missing, report.stale), (1, 0, 0));
+    }
+
+    #[test]
+    fn rebuild_index_repairs_drift() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+        assert!(matches!(storage.verify_index(), Err(AppError::ConfigError(_))));
+        storage.add_item(&id("note"), b"alpha", None).unwrap();
+        storage.rebuild_index().unwrap();
+
+        // Content changed behind the storage's back.
+        fs::write(ctx.storage_root().join("note").join(ITEM_FILE_NAME), b"omega").unwrap();
+        let report = storage.verify_index().unwrap();
+        assert_eq!((report.missing, report.stale), (3, 3));
+
+        storage.rebuild_index().unwrap();
+        let report = storage.verify_index().unwrap();
+        assert_eq!((report.missing, report.stale), (0, 0));
+        assert_eq!(candidates(&storage, "omega").unwrap(), ["note"]);
+        assert!(stray_entries(&ctx.storage_root()).is_empty());
+    }
+
+    #[test]
+    fn list_items_returns_all_ids() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("first"), b"one", None).unwrap();
+        storage.add_item(&id("second"), b"two", None).unwrap();
+
+        let mut items = storage.list_items().expect("list_items succeeds");
+        items.sort();
+        assert_eq!(items, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn namespaced_ids_map_to_nested_directories() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("team/service/token"), b"secret", None).unwrap();
+        storage.add_item(&id("team/readme"), b"docs", None).unwrap();
+        storage.add_item(&id("solo"), b"flat", None).unwrap();
+
+        let root = ctx.storage_root();
+        assert_eq!(
+            fs::read(root.join("team/service/token").join(ITEM_FILE_NAME)).unwrap(),
+            b"secret"
+        );
+        assert_eq!(storage.list_items().unwrap(), ["solo", "team/readme", "team/service/token"]);
+        assert_eq!(storage.get_item(&id("team/service/token")).unwrap(), b"secret");
+
+        // Directories no identifier could name are left out of listings.
+        fs::create_dir(root.join("has space")).unwrap();
+        fs::write(root.join("has space").join(ITEM_FILE_NAME), "stray").unwrap();
+        assert_eq!(storage.list_items().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn items_and_namespaces_cannot_share_a_path() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("team/token"), b"one", None).unwrap();
+        storage.add_item(&id("solo"), b"two", None).unwrap();
+
+        let result = storage.add_item(&id("team"), b"x", None);
+        assert!(
+            matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("namespace"))
+        );
+        let result = storage.add_item(&id("solo/child"), b"x", None);
+        assert!(
+            matches!(result, Err(AppError::ConfigError(ref message)) if message.contains("'solo' is an item"))
+        );
+        assert!(matches!(storage.delete_item(&id("team")), Err(AppError::ItemNotFound(_))));
+
+        // Namespaces disappear with their last item, freeing the name.
+        storage.delete_item(&id("team/token")).unwrap();
+        assert!(!ctx.storage_root().join("team").exists());
+        storage.add_item(&id("team"), b"now an item", None).unwrap();
+        assert!(matches!(storage.restore_item(&id("team/token")), Err(AppError::ConfigError(_))));
+
+        storage.purge_item(&id("team")).unwrap();
+        storage.restore_item(&id("team/token")).unwrap();
+        assert_eq!(storage.get_item(&id("team/token")).unwrap(), b"one");
+        assert_eq!(storage.list_trash().unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn ids_differing_only_in_case_are_refused() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("Foo"), b"upper", None).unwrap();
+        storage.add_item(&id("team/readme"), b"docs", None).unwrap();
+
+        let result = storage.add_item(&id("foo"), b"lower", None);
+        assert!(
+            matches!(result, Err(AppError::CaseCollision(ref new, ref existing)) if new == "foo" && existing == "Foo")
+        );
+        let result = storage.add_item(&id("Team/notes"), b"x", None);
+        assert!(
+            matches!(result, Err(AppError::CaseCollision(_, ref existing)) if existing == "team")
+        );
+        storage.add_item(&id("team/Notes"), b"fine", None).unwrap();
+
+        storage.delete_item(&id("Foo")).unwrap();
+        storage.add_item(&id("FOO"), b"other", None).unwrap();
+        assert!(matches!(storage.restore_item(&id("Foo")), Err(AppError::CaseCollision(..))));
+        assert_eq!(storage.list_items().unwrap(), ["FOO", "team/Notes", "team/readme"]);
+    }
+
+    #[test]
+    fn case_insensitive_filesystems_cannot_write_through_another_spelling() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage().with_case_insensitive_paths();
+
+        storage.add_item(&id("Foo"), b"upper", None).unwrap();
+        assert_eq!(storage.item_dir("foo"), ctx.storage_root().join("Foo"));
+
+        assert!(matches!(
+            storage.add_item(&id("foo"), b"x", None),
+            Err(AppError::CaseCollision(..))
+        ));
+        assert!(matches!(
+            storage.update_item(&id("foo"), b"lower", None),
+            Err(AppError::CaseCollision(..))
+        ));
+        assert!(matches!(storage.delete_item(&id("FOO")), Err(AppError::CaseCollision(..))));
+        assert_eq!(storage.get_item(&id("Foo")).unwrap(), b"upper");
+
+        storage.update_item(&id("Foo"), b"updated", None).unwrap();
+        assert_eq!(storage.get_item(&id("Foo")).unwrap(), b"updated");
+        assert_eq!(storage.list_items().unwrap(), ["Foo"]);
+    }
+
+    #[test]
+    fn delete_item_moves_directory_to_trash() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("temp"), b"data", None).unwrap();
+        storage.add_item(&id("kept"), b"other", None).unwrap();
+        storage.delete_item(&id("temp")).expect("delete succeeds");
+
+        assert!(!ctx.storage_root().join("temp").exists());
+        assert_eq!(storage.list_items().unwrap(), vec!["kept"]);
+        let trash = storage.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, "temp");
+        assert_eq!(trash[0].size, 4);
+    }
+
+    #[test]
+    fn purge_item_bypasses_trash() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("temp"), b"data", None).unwrap();
+        storage.purge_item(&id("temp")).expect("purge succeeds");
+
+        assert!(!ctx.storage_root().join("temp").exists());
+        assert!(storage.list_trash().unwrap().is_empty());
+        assert!(stray_entries(&ctx.storage_root()).is_empty());
+    }
+
+    #[test]
+    fn restore_item_brings_back_latest_deletion() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("temp"), b"first", None).unwrap();
+        storage.delete_item(&id("temp")).unwrap();
+        storage.add_item(&id("temp"), b"second", None).unwrap();
+        storage.update_item(&id("temp"), b"second, edited", None).unwrap();
+
+        let result = storage.restore_item(&id("temp"));
+        assert!(matches!(result, Err(AppError::ItemAlreadyExists(ref id)) if id == "temp"));
+
+        storage.delete_item(&id("temp")).unwrap();
+        storage.restore_item(&id("temp")).expect("restore succeeds");
+
+        assert_eq!(storage.get_item(&id("temp")).unwrap(), b"second, edited");
+        assert_eq!(storage.list_versions(&id("temp")).unwrap().len(), 2);
+        let trash = storage.list_trash().unwrap();
+        assert_eq!(trash.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["temp"]);
+        assert_eq!(trash[0].size, 5);
+    }
+
+    #[test]
+    fn restore_item_fails_if_not_in_trash() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        let result = storage.restore_item(&id("missing"));
+        assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "missing"));
+    }
+
+    #[test]
+    fn empty_trash_respects_age_cutoff() {
+        let ctx = TestContext::new();
+        let storage = ctx.storage();
+
+        storage.add_item(&id("old"), b"one", None).unwrap();
+        storage.add_item(&id("new"), b"two", None).unwrap();
+        storage.delete_item(&id("new")).unwrap();
+        // Backdate one deletion by renaming its trash entry.
+        fs::create_dir_all(ctx.storage_root().join(".trash")).unwrap();
+        fs::rename(
+            ctx.storage_root().join("old"),
+            ctx.storage_root().join(".trash").join("1000000000000000000-old"),
+        )
+        .unwrap();
+
+        let removed = storage.empty_trash(Some(Duration::from_secs(3600))).unwrap();
+        assert_eq!(removed.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["old"]);
+        assert_eq!(removed[0].deleted_at_rfc3339(), "2001-09-09T01:46:40Z");
+
+        let removed = storage.empty_trash(None).unwrap();
+        assert_eq!(removed.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["new"]);
+        assert!(storage.list_trash().unwrap().is_empty());
     }
 
     #[test]
-    #[serial]
     fn delete_item_fails_if_not_exists() {
         let ctx = TestContext::new();
         let storage = ctx.storage();
 
-        let result = storage.delete_item("nonexistent");
+        let result = storage.delete_item(&id("nonexistent"));
         assert!(matches!(result, Err(AppError::ItemNotFound(ref id)) if id == "nonexistent"));
     }
 }
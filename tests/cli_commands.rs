@@ -3,6 +3,7 @@ mod common;
 use common::TestContext;
 use predicates::prelude::*;
 use serial_test::serial;
+use std::fs;
 
 #[test]
 #[serial]
@@ -74,8 +75,199 @@ fn add_with_invalid_id_fails() {
     let ctx = TestContext::new();
 
     ctx.cli()
-        .args(["add", "invalid/id", "--content", "value"])
+        .args(["add", "invalid/../id", "--content", "value"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("invalid item identifier"));
 }
+
+#[test]
+#[serial]
+fn show_command_prints_raw_content() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "demo", "--content", "line one\nline two"]).assert().success();
+
+    ctx.cli().args(["show", "demo"]).assert().success().stdout("line one\nline two");
+    ctx.cli().args(["cat", "demo"]).assert().success().stdout("line one\nline two");
+}
+
+#[test]
+#[serial]
+fn show_nonexistent_item_fails() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["show", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'missing' was not found"));
+}
+
+#[test]
+#[serial]
+fn add_existing_item_fails_without_force() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "demo", "--content", "original"]).assert().success();
+
+    ctx.cli()
+        .args(["add", "demo", "--content", "replacement"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'demo' already exists"));
+
+    ctx.assert_saved_item_contains("demo", "original");
+
+    ctx.cli().args(["add", "demo", "--content", "replacement", "--force"]).assert().success();
+
+    ctx.assert_saved_item_contains("demo", "replacement");
+}
+
+#[test]
+#[serial]
+fn update_command_replaces_existing_item() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["update", "demo", "--content", "value"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'demo' was not found"));
+
+    ctx.cli().args(["add", "demo", "--content", "original"]).assert().success();
+
+    ctx.cli()
+        .args(["update", "demo", "--content", "replacement"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated item 'demo'"));
+
+    ctx.cli().args(["show", "demo"]).assert().success().stdout("replacement");
+}
+
+#[test]
+#[serial]
+fn append_command_extends_existing_item() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "log", "--content", "one\n"]).assert().success();
+
+    ctx.cli()
+        .args(["append", "log", "--content", "two\n"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Appended to item 'log'"));
+
+    ctx.cli().args(["show", "log"]).assert().success().stdout("one\ntwo\n");
+}
+
+#[test]
+#[serial]
+fn list_long_shows_item_metadata() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["add", "demo", "--content", "hello", "--content-type", "text/plain"])
+        .assert()
+        .success();
+
+    assert!(ctx.saved_item_path("demo").with_file_name("meta.json").is_file());
+
+    ctx.cli().args(["list", "--long"]).assert().success().stdout(
+        predicate::str::contains("- demo  5 bytes  updated ")
+            .and(predicate::str::contains("text/plain")),
+    );
+}
+
+#[test]
+#[serial]
+fn list_command_supports_machine_formats() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "first", "--content", "one"]).assert().success();
+    ctx.cli().args(["add", "second", "--content", "two"]).assert().success();
+
+    ctx.cli()
+        .args(["list", "--format", "ndjson"])
+        .assert()
+        .success()
+        .stdout("{\"id\":\"first\"}\n{\"id\":\"second\"}\n");
+
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("first\nsecond\n");
+
+    ctx.cli()
+        .args(["list", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"event\": \"listing\""));
+}
+
+#[test]
+#[serial]
+fn machine_format_errors_are_structured() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["delete", "missing", "--format", "ndjson"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("{\"error\":{\"code\":\"item_not_found\""));
+}
+
+#[test]
+#[serial]
+fn store_flag_overrides_default_root() {
+    let ctx = TestContext::new();
+    let store = ctx.create_workspace("custom-store");
+
+    ctx.cli()
+        .arg("--store")
+        .arg(&store)
+        .args(["add", "demo", "--content", "value"])
+        .assert()
+        .success();
+
+    assert!(store.join("demo").join("item.txt").is_file());
+    assert!(!ctx.saved_item_path("demo").exists());
+}
+
+#[test]
+#[serial]
+fn store_root_is_resolved_from_environment() {
+    let ctx = TestContext::new();
+    let env_store = ctx.create_workspace("env-store");
+    let xdg_data = ctx.create_workspace("xdg-data");
+
+    ctx.cli()
+        .env("XDG_DATA_HOME", &xdg_data)
+        .args(["add", "from-xdg", "--content", "value"])
+        .assert()
+        .success();
+    assert!(xdg_data.join("rs-cli-tmpl").join("from-xdg").join("item.txt").is_file());
+
+    ctx.cli()
+        .env("XDG_DATA_HOME", &xdg_data)
+        .env("RS_CLI_TMPL_HOME", &env_store)
+        .args(["add", "from-env", "--content", "value"])
+        .assert()
+        .success();
+    assert!(env_store.join("from-env").join("item.txt").is_file());
+}
+
+#[test]
+#[serial]
+fn writes_clean_up_interrupted_writes_that_reads_ignore() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "demo", "--content", "value"]).assert().success();
+
+    let orphan = ctx.store_root().join(".tmp-1-2-0");
+    fs::create_dir(&orphan).unwrap();
+    fs::write(orphan.join("item.txt"), "half written").unwrap();
+
+    ctx.cli().args(["list"]).assert().success().stdout("📦 Stored items:\n- demo\n");
+    assert!(orphan.exists());
+    ctx.cli().args(["add", "next", "--content", "value"]).assert().success();
+    assert!(!orphan.exists());
+}
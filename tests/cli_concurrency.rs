@@ -0,0 +1,108 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs::{self, OpenOptions};
+use std::process::{Child, Stdio};
+
+const WORKERS: usize = 24;
+
+fn spawn(ctx: &TestContext, args: &[&str]) -> Child {
+    ctx.process()
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn rs-cli-tmpl")
+}
+
+fn wait_all(children: Vec<Child>) {
+    for child in children {
+        let output = child.wait_with_output().expect("failed to wait for rs-cli-tmpl");
+        assert!(
+            output.status.success(),
+            "concurrent invocation failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn concurrent_appends_are_never_lost() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "shared", "--content", ""]).assert().success();
+
+    let lines: Vec<String> = (0..WORKERS).map(|worker| format!("worker-{worker}\n")).collect();
+    let children =
+        lines.iter().map(|line| spawn(&ctx, &["append", "shared", "--content", line])).collect();
+    wait_all(children);
+
+    let content = fs::read_to_string(ctx.saved_item_path("shared")).unwrap();
+    let mut written: Vec<&str> = content.lines().collect();
+    written.sort();
+    let mut expected: Vec<&str> = lines.iter().map(|line| line.trim_end()).collect();
+    expected.sort();
+    assert_eq!(written, expected);
+}
+
+#[test]
+#[serial]
+fn concurrent_writers_readers_and_deleters_stay_consistent() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "stable", "--content", "unchanged"]).assert().success();
+
+    let mut children = Vec::new();
+    for worker in 0..WORKERS {
+        let id = format!("item-{worker}");
+        let content = format!("payload {worker}");
+        children.push(spawn(&ctx, &["add", &id, "--content", &content]));
+        children.push(spawn(&ctx, &["show", "stable"]));
+        children.push(spawn(&ctx, &["list", "--long"]));
+    }
+    wait_all(children);
+
+    let children = (0..WORKERS)
+        .filter(|worker| worker % 2 == 0)
+        .map(|worker| spawn(&ctx, &["delete", &format!("item-{worker}")]))
+        .collect();
+    wait_all(children);
+
+    let mut expected: Vec<String> = (0..WORKERS)
+        .filter(|worker| worker % 2 == 1)
+        .map(|worker| format!("item-{worker}"))
+        .collect();
+    expected.push("stable".to_string());
+    expected.sort();
+    let listing = ctx.cli().args(["--format", "tsv", "list"]).output().unwrap();
+    let listed: Vec<String> =
+        String::from_utf8(listing.stdout).unwrap().lines().map(str::to_string).collect();
+    assert_eq!(listed, expected);
+
+    for id in &expected[..expected.len() - 1] {
+        let worker = id.trim_start_matches("item-");
+        ctx.cli().args(["show", id]).assert().success().stdout(format!("payload {worker}"));
+    }
+}
+
+#[test]
+#[serial]
+fn held_lock_times_out_with_structured_error() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "demo", "--content", "value"]).assert().success();
+
+    let lock = OpenOptions::new().write(true).open(ctx.store_root().join(".lock")).unwrap();
+    lock.lock().unwrap();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_LOCK_TIMEOUT", "200ms")
+        .args(["--format", "json", "update", "demo", "--content", "blocked"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"lock_timeout\""));
+
+    lock.unlock().unwrap();
+    ctx.cli().args(["update", "demo", "--content", "free"]).assert().success();
+    ctx.cli().args(["show", "demo"]).assert().success().stdout("free");
+}
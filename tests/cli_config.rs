@@ -0,0 +1,147 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[test]
+#[serial]
+fn config_set_persists_value_and_get_reads_it_back() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["config", "set", "editor", "nano"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set editor = nano"));
+
+    let text = fs::read_to_string(ctx.config_path()).expect("config file should exist");
+    assert!(text.contains("editor = \"nano\""), "unexpected config contents: {text}");
+
+    ctx.cli()
+        .env_remove("VISUAL")
+        .env_remove("EDITOR")
+        .args(["config", "get", "editor"])
+        .assert()
+        .success()
+        .stdout("nano\n");
+}
+
+#[test]
+#[serial]
+fn config_help_lists_every_setting() {
+    let ctx = TestContext::new();
+
+    for action in ["get", "set"] {
+        let assert = ctx.cli().args(["config", action, "--help"]).assert().success();
+        let help = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        for key in rs_cli_tmpl::cli::KEYS {
+            assert!(help.contains(key), "`config {action} --help` omits {key}:\n{help}");
+        }
+    }
+}
+
+#[test]
+#[serial]
+fn config_set_rejects_unknown_keys_and_invalid_values() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["config", "set", "colour", "never"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown config key 'colour'"));
+
+    ctx.cli()
+        .args(["config", "set", "format", "xml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'xml' for format"));
+}
+
+#[test]
+#[serial]
+fn config_format_applies_unless_overridden() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "demo", "--content", "value"]).assert().success();
+    ctx.cli().args(["config", "set", "format", "tsv"]).assert().success();
+
+    ctx.cli().arg("list").assert().success().stdout("demo\n");
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_FORMAT", "ndjson")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout("{\"id\":\"demo\"}\n");
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_FORMAT", "ndjson")
+        .args(["list", "--format", "human"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- demo"));
+}
+
+#[test]
+#[serial]
+fn config_list_reports_sources() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["config", "set", "confirm", "true"]).assert().success();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_COLOR", "never")
+        .args(["config", "list", "--format", "tsv"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("format\ttsv\tflag\n")
+                .and(predicate::str::contains("confirm\ttrue\tfile\n"))
+                .and(predicate::str::contains("color\tnever\tenv\n"))
+                .and(predicate::str::contains(format!(
+                    "store\t{}\tdefault\n",
+                    ctx.store_root().display()
+                ))),
+        );
+}
+
+#[test]
+#[serial]
+fn config_path_prints_config_location() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["config", "path"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", ctx.config_path().display()));
+}
+
+#[test]
+#[serial]
+fn confirm_setting_prompts_before_delete() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "temp", "--content", "value"]).assert().success();
+    ctx.cli().args(["config", "set", "confirm", "true"]).assert().success();
+
+    ctx.cli()
+        .args(["delete", "temp"])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Aborted, kept 'temp'"));
+    ctx.cli()
+        .args(["--format", "ndjson", "delete", "temp"])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout("{\"event\":\"delete_aborted\",\"id\":\"temp\"}\n");
+    assert!(ctx.saved_item_path("temp").exists(), "Declined delete should keep the item");
+
+    ctx.cli().args(["delete", "temp"]).write_stdin("y\n").assert().success();
+    assert!(!ctx.saved_item_path("temp").exists(), "Confirmed delete should remove the item");
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[test]
+#[serial]
+fn doctor_reports_names_differing_only_in_case() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "Foo", "--content", "upper"]).assert().success();
+    ctx.cli().args(["add", "team/readme", "--content", "docs"]).assert().success();
+    ctx.cli().args(["doctor"]).assert().success().stdout("🩺 No problems found\n");
+
+    // Written by hand, as a case-sensitive copy of the store could hold it.
+    let lower = ctx.store_root().join("foo");
+    fs::create_dir(&lower).unwrap();
+    fs::copy(ctx.saved_item_path("Foo"), lower.join("item.txt")).unwrap();
+
+    ctx.cli()
+        .args(["--format", "tsv", "doctor"])
+        .assert()
+        .failure()
+        .stdout("case_collision\tFoo\tfoo\n")
+        .stderr(predicate::str::contains("problems_found\tFound 1 problem in the store"));
+
+    ctx.cli().args(["delete", "--purge", "--yes", "foo"]).assert().success();
+    ctx.cli().args(["doctor"]).assert().success();
+}
+
+#[test]
+#[serial]
+fn new_ids_must_not_differ_only_in_case_from_existing_names() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "Foo", "--content", "upper"]).assert().success();
+    ctx.cli().args(["add", "team/readme", "--content", "docs"]).assert().success();
+
+    ctx.cli()
+        .args(["--format", "json", "add", "foo", "--content", "lower", "--force"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"case_collision\""));
+    ctx.cli()
+        .args(["add", "TEAM/notes", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'TEAM/notes' differs only in letter case from 'team'"));
+    ctx.cli().args(["show", "Foo"]).assert().success().stdout("upper");
+}
+
+#[test]
+#[serial]
+fn id_fold_case_lowercases_every_identifier() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_ID_FOLD_CASE", "true")
+        .args(["add", "Team/ReadMe", "--content", "docs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added item 'team/readme'"));
+    ctx.cli()
+        .env("RS_CLI_TMPL_ID_FOLD_CASE", "true")
+        .args(["show", "TEAM/README"])
+        .assert()
+        .success()
+        .stdout("docs");
+    ctx.assert_saved_item_contains("team/readme", "docs");
+}
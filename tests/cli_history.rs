@@ -0,0 +1,70 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn versions(ctx: &TestContext, id: &str) -> Vec<String> {
+    let output = ctx.cli().args(["--format", "tsv", "history", id]).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split('\t').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+#[serial]
+fn history_show_version_and_rollback_flow() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "note", "--content", "first"]).assert().success();
+    ctx.cli().args(["update", "note", "--content", "second"]).assert().success();
+    ctx.cli().args(["append", "note", "--content", "!"]).assert().success();
+
+    assert_eq!(versions(&ctx, "note"), ["1", "2", "3"]);
+    ctx.cli()
+        .args(["history", "note"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("History of item 'note'"))
+        .stdout(predicate::str::contains("- v3  7 bytes"))
+        .stdout(predicate::str::contains("(current)"));
+
+    ctx.cli().args(["show", "note", "--version", "1"]).assert().success().stdout("first");
+    ctx.cli().args(["show", "note", "--version", "3"]).assert().success().stdout("second!");
+
+    ctx.cli()
+        .args(["rollback", "note", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Rolled back item 'note' to version 1"));
+    ctx.cli().args(["show", "note"]).assert().success().stdout("first");
+    assert_eq!(versions(&ctx, "note"), ["1", "2", "3", "4"]);
+}
+
+#[test]
+#[serial]
+fn history_limit_setting_bounds_retained_versions() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "note", "--content", "v1"]).assert().success();
+    for content in ["v2", "v3", "v4"] {
+        ctx.cli()
+            .env("RS_CLI_TMPL_HISTORY_LIMIT", "1")
+            .args(["update", "note", "--content", content])
+            .assert()
+            .success();
+    }
+
+    assert_eq!(versions(&ctx, "note"), ["3", "4"]);
+    ctx.cli()
+        .args(["--format", "json", "show", "note", "--version", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"version_not_found\""));
+    ctx.cli()
+        .args(["rollback", "note", "9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'note' has no version 9"));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn identifiers_accept_dots_and_underscores_and_explain_rejections() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "app_config.v2", "--content", "x"]).assert().success();
+    ctx.cli().args(["show", "app_config.v2"]).assert().success().stdout("x");
+
+    ctx.cli()
+        .args(["add", ".trash", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("segment '.trash' starts with '.'"));
+    ctx.cli()
+        .args(["--format", "json", "add", "has space", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"invalid_item_id\""))
+        .stderr(predicate::str::contains("' ' is not allowed"));
+    ctx.cli()
+        .args(["add", "p\u{430}ypal", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("(U+0430) is not an ASCII letter or digit"));
+}
+
+#[test]
+#[serial]
+fn id_settings_widen_the_charset_and_bound_the_length() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_ID_CHARSET", "unicode")
+        .args(["add", "cafe\u{301}", "--content", "noir"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added item 'caf\u{e9}'"));
+    ctx.cli()
+        .env("RS_CLI_TMPL_ID_CHARSET", "unicode")
+        .args(["show", "caf\u{e9}"])
+        .assert()
+        .success()
+        .stdout("noir");
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_ID_MAX_LENGTH", "4")
+        .args(["add", "toolong", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("7 bytes long, more than the limit of 4"));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[test]
+#[serial]
+fn index_rebuild_and_verify_flow() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "groceries", "--content", "Buy milk"]).assert().success();
+
+    ctx.cli()
+        .args(["index", "verify"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no search index"));
+    ctx.cli()
+        .args(["index", "rebuild"])
+        .assert()
+        .success()
+        .stdout("🔧 Rebuilt search index: 1 items, 3 terms\n");
+
+    ctx.cli().args(["add", "shake", "--content", "milkshake"]).assert().success();
+    ctx.cli().args(["delete", "groceries"]).assert().success();
+    ctx.cli()
+        .args(["--format", "tsv", "search", "milk"])
+        .assert()
+        .success()
+        .stdout("shake\t1\tmilkshake\n");
+    ctx.cli()
+        .args(["--format", "tsv", "index", "verify"])
+        .assert()
+        .success()
+        .stdout("verified\t1\t7\n");
+
+    fs::write(ctx.saved_item_path("shake"), "changed behind our back").unwrap();
+    ctx.cli()
+        .args(["--format", "json", "index", "verify"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"index_out_of_date\""));
+    ctx.cli().args(["index", "rebuild"]).assert().success();
+    ctx.cli().args(["index", "verify"]).assert().success();
+    ctx.cli()
+        .args(["--format", "tsv", "search", "behind"])
+        .assert()
+        .success()
+        .stdout("shake\t1\tchanged behind our back\n");
+}
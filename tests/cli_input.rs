@@ -0,0 +1,110 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[test]
+#[serial]
+fn add_reads_content_from_stdin() {
+    let ctx = TestContext::new();
+
+    ctx.cli().args(["add", "dash", "--content", "-"]).write_stdin("from dash\n").assert().success();
+    ctx.assert_saved_item_contains("dash", "from dash\n");
+
+    ctx.cli().args(["add", "piped"]).write_stdin("line one\nline two\n").assert().success();
+    ctx.cli().args(["show", "piped"]).assert().success().stdout("line one\nline two\n");
+}
+
+#[test]
+#[serial]
+fn add_reads_content_from_file() {
+    let ctx = TestContext::new();
+    ctx.write_item_file("multi\nline\npayload\n");
+
+    ctx.cli().args(["add", "from-file", "--from-file", "item.txt"]).assert().success();
+
+    ctx.cli().args(["show", "from-file"]).assert().success().stdout("multi\nline\npayload\n");
+}
+
+#[test]
+#[serial]
+fn content_and_from_file_are_mutually_exclusive() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .args(["add", "demo", "--content", "value", "--from-file", "item.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn edit_command_saves_editor_changes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new();
+    let script = ctx.work_dir().join("editor.sh");
+    fs::write(&script, "#!/bin/sh\necho 'edited line' >> \"$1\"\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    ctx.cli().args(["add", "note", "--content", "original\n"]).assert().success();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_EDITOR", &script)
+        .args(["edit", "note"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated item 'note'"));
+
+    ctx.cli().args(["show", "note"]).assert().success().stdout("original\nedited line\n");
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_EDITOR", "true")
+        .args(["edit", "note"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No changes to item 'note'"));
+}
+
+#[test]
+#[serial]
+fn edit_missing_item_fails() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_EDITOR", "true")
+        .args(["edit", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'missing' was not found"));
+}
+
+#[test]
+#[serial]
+fn binary_content_round_trips_through_the_cli() {
+    let ctx = TestContext::new();
+    let payload: Vec<u8> = (0..=255).collect();
+    fs::write(ctx.work_dir().join("blob.bin"), &payload).unwrap();
+
+    ctx.cli().args(["add", "blob", "--from-file", "blob.bin"]).assert().success();
+
+    ctx.cli().args(["show", "blob"]).assert().success().stdout(payload.clone());
+    ctx.cli().args(["show", "blob", "--raw"]).assert().success().stdout(payload);
+
+    ctx.cli()
+        .args(["--format", "json", "show", "blob"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"content_base64\": \"AAECAwQF"));
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_EDITOR", "true")
+        .args(["edit", "blob"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("contains binary data"));
+}
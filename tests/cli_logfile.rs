@@ -0,0 +1,82 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs::{self, OpenOptions};
+
+#[test]
+#[serial]
+fn logfile_backend_keeps_the_store_in_one_file() {
+    let ctx = TestContext::new();
+    let cli = || {
+        let mut cmd = ctx.cli();
+        cmd.args(["--backend", "logfile"]);
+        cmd
+    };
+
+    cli().args(["add", "team/notes", "--content", "first", "--tag", "work"]).assert().success();
+    cli().args(["update", "team/notes", "--content", "second"]).assert().success();
+    cli().args(["show", "team/notes", "--version", "1"]).assert().success().stdout("first");
+    cli().args(["delete", "--yes", "team/notes"]).assert().success();
+    cli().args(["restore", "team/notes"]).assert().success();
+    cli().args(["show", "team/notes"]).assert().success().stdout("second");
+
+    assert!(ctx.store_root().join("store.log").is_file());
+    assert!(!ctx.saved_item_path("team/notes").exists());
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("");
+}
+
+#[test]
+#[serial]
+fn compact_reclaims_space_from_superseded_records() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .env("RS_CLI_TMPL_BACKEND", "logfile")
+        .env("RS_CLI_TMPL_HISTORY_LIMIT", "0")
+        .args(["add", "note", "--content", "draft"])
+        .assert()
+        .success();
+    for content in ["second draft", "final"] {
+        ctx.cli()
+            .env("RS_CLI_TMPL_BACKEND", "logfile")
+            .env("RS_CLI_TMPL_HISTORY_LIMIT", "0")
+            .args(["update", "note", "--content", content])
+            .assert()
+            .success();
+    }
+    let log = ctx.store_root().join("store.log");
+    let before = fs::metadata(&log).unwrap().len();
+
+    let compacted =
+        ctx.cli().args(["--backend", "logfile", "--format", "tsv", "compact"]).assert().success();
+    let after = fs::metadata(&log).unwrap().len();
+    assert!(after < before, "log grew from {before} to {after} bytes");
+    compacted.stdout(format!("compacted\t{before}\t{after}\n"));
+    ctx.cli().args(["--backend", "logfile", "show", "note"]).assert().success().stdout("final");
+
+    ctx.cli()
+        .args(["compact"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only the logfile backend keeps a log to compact"));
+}
+
+#[test]
+#[serial]
+fn torn_tail_write_is_dropped_on_the_next_run() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["--backend", "logfile", "add", "kept", "--content", "x"]).assert().success();
+    ctx.cli().args(["--backend", "logfile", "add", "torn", "--content", "y"]).assert().success();
+    let log = ctx.store_root().join("store.log");
+    let len = fs::metadata(&log).unwrap().len();
+    OpenOptions::new().write(true).open(&log).unwrap().set_len(len - 3).unwrap();
+
+    ctx.cli()
+        .args(["--backend", "logfile", "--format", "tsv", "list"])
+        .assert()
+        .success()
+        .stdout("kept\n");
+    ctx.cli().args(["--backend", "logfile", "add", "torn", "--content", "z"]).assert().success();
+    ctx.cli().args(["--backend", "logfile", "show", "torn"]).assert().success().stdout("z");
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn add_tree(ctx: &TestContext) {
+    for id in ["team/service/token", "team/service/key", "team/readme", "solo"] {
+        ctx.cli().args(["add", id, "--content", id]).assert().success();
+    }
+}
+
+#[test]
+#[serial]
+fn namespaced_ids_are_stored_nested_and_browsed_by_prefix() {
+    let ctx = TestContext::new();
+    add_tree(&ctx);
+
+    ctx.assert_saved_item_contains("team/service/token", "team/service/token");
+    ctx.cli().args(["show", "team/service/token"]).assert().success().stdout("team/service/token");
+
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("solo\nteam/\n");
+    ctx.cli()
+        .args(["--format", "tsv", "list", "team"])
+        .assert()
+        .success()
+        .stdout("team/readme\nteam/service/\n");
+    ctx.cli()
+        .args(["--format", "tsv", "list", "team", "--recursive"])
+        .assert()
+        .success()
+        .stdout("team/readme\nteam/service/key\nteam/service/token\n");
+    ctx.cli()
+        .args(["list", "team/service"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- team/service/key\n- team/service/token\n"));
+
+    ctx.cli()
+        .args(["add", "../escape", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid item identifier '../escape': segment '..'"));
+    ctx.cli()
+        .args(["add", "solo/child", "--content", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'solo' is an item"));
+}
+
+#[test]
+#[serial]
+fn recursive_delete_asks_before_trashing_a_namespace() {
+    let ctx = TestContext::new();
+    add_tree(&ctx);
+
+    ctx.cli()
+        .args(["--format", "tsv", "delete", "-r", "team/service"])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Delete every item under 'team/service'? [y/N]"))
+        .stdout("aborted\tteam/service\n");
+    ctx.cli()
+        .args(["--format", "tsv", "list", "-r"])
+        .assert()
+        .success()
+        .stdout("solo\nteam/readme\nteam/service/key\nteam/service/token\n");
+
+    ctx.cli()
+        .args(["--format", "tsv", "delete", "-r", "team/service"])
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout("trashed\tteam/service/key\ntrashed\tteam/service/token\n");
+    ctx.cli().args(["--format", "tsv", "list", "team"]).assert().success().stdout("team/readme\n");
+    assert!(!ctx.store_root().join("team").join("service").exists());
+
+    ctx.cli().args(["restore", "team/service/token"]).assert().success();
+    ctx.cli().args(["show", "team/service/token"]).assert().success().stdout("team/service/token");
+
+    ctx.cli()
+        .args(["delete", "-r", "missing", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'missing' was not found"));
+
+    for prefix in ["/", "", "team//"] {
+        ctx.cli()
+            .args(["--format", "tsv", "delete", "-r", prefix, "--yes"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::starts_with("error\tinvalid_item_id\t"));
+    }
+    ctx.cli()
+        .args(["--format", "tsv", "list", "-r"])
+        .assert()
+        .success()
+        .stdout("solo\nteam/readme\nteam/service/token\n");
+}
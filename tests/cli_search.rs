@@ -0,0 +1,51 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn seed(ctx: &TestContext) {
+    ctx.cli().args(["add", "groceries", "--content", "Buy milk\nbuy bread\n"]).assert().success();
+    ctx.cli().args(["add", "release", "--content", "ship v2.1\nthen v2.2"]).assert().success();
+}
+
+#[test]
+#[serial]
+fn search_reports_ids_lines_and_snippets() {
+    let ctx = TestContext::new();
+    seed(&ctx);
+
+    ctx.cli()
+        .args(["search", "milk"])
+        .assert()
+        .success()
+        .stdout("🔎 1 match for 'milk':\ngroceries:1: Buy milk\n");
+
+    ctx.cli()
+        .args(["--format", "tsv", "search", "-i", "BUY"])
+        .assert()
+        .success()
+        .stdout("groceries\t1\tBuy milk\ngroceries\t2\tbuy bread\n");
+
+    ctx.cli()
+        .args(["--format", "ndjson", "search", "--regex", r"v2\.\d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"id\":\"release\",\"line\":1"))
+        .stdout(predicate::str::contains("\"line\":2,\"text\":\"then v2.2\",\"spans\":[[5,9]]"));
+
+    ctx.cli().args(["search", "v2.1.0"]).assert().success().stdout("🔎 No matches for 'v2.1.0'\n");
+}
+
+#[test]
+#[serial]
+fn search_rejects_invalid_patterns() {
+    let ctx = TestContext::new();
+    seed(&ctx);
+
+    ctx.cli()
+        .args(["--format", "json", "search", "--regex", "("])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid search pattern"));
+}
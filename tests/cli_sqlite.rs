@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn sqlite_backend_keeps_the_store_in_one_database_file() {
+    let ctx = TestContext::new();
+    let cli = || {
+        let mut cmd = ctx.cli();
+        cmd.args(["--backend", "sqlite"]);
+        cmd
+    };
+
+    cli().args(["add", "team/notes", "--content", "first", "--tag", "work"]).assert().success();
+    cli().args(["append", "team/notes", "--content", " draft"]).assert().success();
+    cli().args(["show", "team/notes"]).assert().success().stdout("first draft");
+    cli()
+        .args(["--format", "tsv", "history", "team/notes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("1\t").and(predicate::str::contains("\n2\t")));
+    cli()
+        .args(["--format", "tsv", "list", "--tag", "work", "--recursive"])
+        .assert()
+        .success()
+        .stdout("team/notes\n");
+    cli()
+        .args(["search", "DRAFT", "--ignore-case"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("team/notes"));
+
+    cli().args(["delete", "--yes", "team/notes"]).assert().success();
+    cli().args(["--format", "tsv", "list"]).assert().success().stdout("");
+    cli().args(["restore", "team/notes"]).assert().success();
+    cli().args(["show", "team/notes"]).assert().success().stdout("first draft");
+
+    assert!(ctx.store_root().join("store.sqlite3").is_file());
+    assert!(!ctx.saved_item_path("team/notes").exists());
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("");
+}
+
+#[test]
+#[serial]
+fn backend_setting_selects_sqlite_from_env_and_config() {
+    let ctx = TestContext::new();
+
+    ctx.cli()
+        .env("RS_CLI_TMPL_BACKEND", "sqlite")
+        .args(["add", "from-env", "--content", "x"])
+        .assert()
+        .success();
+    ctx.cli().args(["config", "set", "backend", "sqlite"]).assert().success();
+    ctx.cli().args(["config", "get", "backend"]).assert().success().stdout("sqlite\n");
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("from-env\n");
+    ctx.cli()
+        .args(["--backend", "filesystem", "--format", "tsv", "list"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+#[serial]
+fn sqlite_backend_refuses_case_collisions_and_index_commands() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["--backend", "sqlite", "add", "Foo", "--content", "x"]).assert().success();
+
+    ctx.cli()
+        .args(["--backend", "sqlite", "--format", "tsv", "add", "foo", "--content", "y"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("error\tcase_collision\t"));
+    ctx.cli().args(["--backend", "sqlite", "doctor"]).assert().success();
+    ctx.cli()
+        .args(["--backend", "sqlite", "index", "rebuild"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only kept by the filesystem backend"));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn listed(ctx: &TestContext, args: &[&str]) -> String {
+    let output = ctx.cli().args(["--format", "tsv", "list"]).args(args).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn tags_filter_listings() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .args(["add", "plan", "--content", "a", "--tag", "work", "--tag", "urgent"])
+        .assert()
+        .success();
+    ctx.cli().args(["add", "notes", "--content", "b", "--tag", "work"]).assert().success();
+    ctx.cli().args(["add", "recipe", "--content", "c"]).assert().success();
+
+    assert_eq!(listed(&ctx, &["--tag", "work"]), "notes\nplan\n");
+    assert_eq!(listed(&ctx, &["--tag", "work", "--tag", "urgent"]), "plan\n");
+    assert_eq!(listed(&ctx, &["--tag", "home"]), "");
+
+    ctx.cli()
+        .args(["tag", "add", "recipe", "home"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tagged item 'recipe' with 'home'"));
+    assert_eq!(listed(&ctx, &["--tag", "home", "--tag", "urgent", "--any-tag"]), "plan\nrecipe\n");
+
+    ctx.cli()
+        .args(["tag", "remove", "plan", "urgent"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed tag 'urgent' from item 'plan'"));
+    ctx.cli()
+        .args(["tag", "remove", "plan", "urgent"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No changes to item 'plan'"));
+    assert_eq!(listed(&ctx, &["--tag", "urgent"]), "");
+
+    ctx.cli().args(["list", "--long", "--tag", "work"]).assert().success().stdout(
+        predicate::str::contains("- plan  1 bytes  updated ")
+            .and(predicate::str::contains("#work")),
+    );
+}
+
+#[test]
+#[serial]
+fn forced_add_replaces_the_tags_of_the_overwritten_item() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .args(["add", "plan", "--content", "a", "--tag", "work", "--tag", "urgent"])
+        .assert()
+        .success();
+    ctx.cli()
+        .args(["add", "plan", "--content", "b", "--force", "--tag", "home"])
+        .assert()
+        .success();
+
+    assert_eq!(listed(&ctx, &["--tag", "home"]), "plan\n");
+    assert_eq!(listed(&ctx, &["--tag", "work", "--tag", "urgent", "--any-tag"]), "");
+}
+
+#[test]
+#[serial]
+fn tag_commands_reject_bad_input() {
+    let ctx = TestContext::new();
+    ctx.cli()
+        .args(["add", "demo", "--content", "x", "--tag", "two words"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid tag: two words"));
+    assert!(!ctx.saved_item_path("demo").exists());
+
+    ctx.cli()
+        .args(["--format", "json", "tag", "add", "missing", "work"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"item_not_found\""));
+    ctx.cli().args(["list", "--any-tag"]).assert().failure();
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::TestContext;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn trashed(ctx: &TestContext) -> Vec<String> {
+    let output = ctx.cli().args(["--format", "tsv", "trash", "list"]).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split('\t').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+#[serial]
+fn delete_moves_item_to_trash_and_restore_brings_it_back() {
+    let ctx = TestContext::new();
+    ctx.cli().args(["add", "note", "--content", "keep me"]).assert().success();
+    ctx.cli().args(["add", "other", "--content", "x"]).assert().success();
+
+    ctx.cli()
+        .args(["delete", "note"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted item 'note' (moved to the trash)"));
+    ctx.cli().args(["--format", "tsv", "list"]).assert().success().stdout("other\n");
+    assert_eq!(trashed(&ctx), ["note"]);
+    ctx.cli()
+        .args(["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- note  7 bytes  deleted "));
+
+    ctx.cli()
+        .args(["restore", "note"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored item 'note'"));
+    ctx.cli().args(["show", "note"]).assert().success().stdout("keep me");
+    assert!(trashed(&ctx).is_empty());
+
+    ctx.cli()
+        .args(["--format", "json", "restore", "note"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"code\": \"item_already_exists\""));
+    ctx.cli()
+        .args(["restore", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Item 'missing' was not found"));
+}
+
+#[test]
+#[serial]
+fn purge_and_trash_empty_remove_items_permanently() {
+    let ctx = TestContext::new();
+    for id in ["one", "two", "three"] {
+        ctx.cli().args(["add", id, "--content", id]).assert().success();
+    }
+
+    ctx.cli()
+        .args(["--format", "tsv", "delete", "one", "--purge"])
+        .assert()
+        .success()
+        .stdout("deleted\tone\n");
+    ctx.cli().args(["delete", "two"]).assert().success();
+    ctx.cli().args(["delete", "three"]).assert().success();
+    assert_eq!(trashed(&ctx), ["two", "three"]);
+
+    ctx.cli()
+        .args(["trash", "empty", "--older-than", "1day"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Permanently removed 0 items from the trash"));
+    assert_eq!(trashed(&ctx).len(), 2);
+
+    ctx.cli()
+        .args(["--format", "tsv", "trash", "empty"])
+        .assert()
+        .success()
+        .stdout("purged\ttwo\npurged\tthree\n");
+    assert!(trashed(&ctx).is_empty());
+    ctx.cli().args(["restore", "two"]).assert().failure();
+    ctx.cli().args(["trash", "list"]).assert().success().stdout("🗑️  Trash:\n(empty)\n");
+}
@@ -1,16 +1,19 @@
 mod common;
 
 use common::TestContext;
-use rs_cli_tmpl::{add, delete, list};
+use rs_cli_tmpl::{AddOptions, DeleteOptions, ListOptions, ShowOptions, add, delete, list, show};
+use rs_cli_tmpl::{CaptureReporter, Event, ListEntry, SilentReporter};
 use serial_test::serial;
 
 #[test]
 #[serial]
 fn add_persists_item_via_library_api() {
     let ctx = TestContext::new();
+    let storage = ctx.storage();
 
     ctx.with_dir(ctx.work_dir(), || {
-        add("sample", "hello world").expect("library add should succeed");
+        add(&storage, &SilentReporter, "sample", "hello world", &AddOptions::default())
+            .expect("library add should succeed");
     });
 
     ctx.assert_saved_item_contains("sample", "hello world");
@@ -20,30 +23,87 @@ fn add_persists_item_via_library_api() {
 #[serial]
 fn list_returns_items_via_library_api() {
     let ctx = TestContext::new();
+    let storage = ctx.storage();
 
     ctx.with_dir(ctx.work_dir(), || {
-        add("first", "one").expect("add should succeed");
-        add("second", "two").expect("add should succeed");
-        let mut items = list().expect("list should succeed");
+        add(&storage, &SilentReporter, "first", "one", &AddOptions::default())
+            .expect("add should succeed");
+        add(&storage, &SilentReporter, "second", "two", &AddOptions::default())
+            .expect("add should succeed");
+        let mut items =
+            list(&storage, &SilentReporter, &ListOptions::default()).expect("list should succeed");
         items.sort();
         assert_eq!(items, vec!["first".to_string(), "second".to_string()]);
     });
 }
 
+#[test]
+#[serial]
+fn show_returns_item_content_via_library_api() {
+    let ctx = TestContext::new();
+    let storage = ctx.storage();
+
+    ctx.with_dir(ctx.work_dir(), || {
+        add(&storage, &SilentReporter, "sample", "hello world", &AddOptions::default())
+            .expect("add should succeed");
+        let reporter = CaptureReporter::default();
+        let written = show(&storage, &reporter, "sample", &ShowOptions::default())
+            .expect("show should succeed");
+        assert_eq!(written, 11);
+        assert_eq!(
+            reporter.take(),
+            vec![Event::ItemContent { id: "sample".to_string(), content: b"hello world".to_vec() }]
+        );
+    });
+}
+
 #[test]
 #[serial]
 fn delete_removes_item_via_library_api() {
     let ctx = TestContext::new();
+    let storage = ctx.storage();
 
     ctx.with_dir(ctx.work_dir(), || {
-        add("temp", "value").expect("add should succeed");
+        add(&storage, &SilentReporter, "temp", "value", &AddOptions::default())
+            .expect("add should succeed");
     });
 
     assert!(ctx.saved_item_path("temp").exists(), "Item should exist before delete");
 
     ctx.with_dir(ctx.work_dir(), || {
-        delete("temp").expect("delete should succeed");
+        delete(&storage, &SilentReporter, "temp", &DeleteOptions::default())
+            .expect("delete should succeed");
     });
 
     assert!(!ctx.saved_item_path("temp").exists(), "Item should be removed after delete");
 }
+
+#[test]
+#[serial]
+fn commands_report_events_to_reporter() {
+    let ctx = TestContext::new();
+    let storage = ctx.storage();
+    let reporter = CaptureReporter::default();
+
+    ctx.with_dir(ctx.work_dir(), || {
+        add(&storage, &reporter, "demo", "value", &AddOptions::default())
+            .expect("add should succeed");
+        list(&storage, &reporter, &ListOptions::default()).expect("list should succeed");
+        delete(
+            &storage,
+            &reporter,
+            "demo",
+            &DeleteOptions { purge: true, ..DeleteOptions::default() },
+        )
+        .expect("delete should succeed");
+    });
+
+    assert_eq!(
+        reporter.events(),
+        vec![
+            Event::ItemAdded { id: "demo".to_string() },
+            Event::Listing { items: vec![ListEntry { id: "demo".to_string(), metadata: None }] },
+            Event::ItemDeleted { id: "demo".to_string() },
+        ]
+    );
+}
@@ -1,7 +1,8 @@
 mod common;
 
 use common::TestContext;
-use rs_cli_tmpl::add;
+use rs_cli_tmpl::SilentReporter;
+use rs_cli_tmpl::{AddOptions, add, update};
 use serial_test::serial;
 use std::io;
 
@@ -9,9 +10,40 @@ use std::io;
 #[serial]
 fn add_with_invalid_id_surfaces_error() {
     let ctx = TestContext::new();
+    let storage = ctx.storage();
 
     ctx.with_dir(ctx.work_dir(), || {
-        let err = add("invalid/id", "content").expect_err("add should fail for invalid id");
+        let err =
+            add(&storage, &SilentReporter, "invalid/../id", "content", &AddOptions::default())
+                .expect_err("add should fail for invalid id");
         assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
     });
 }
+
+#[test]
+#[serial]
+fn add_existing_item_surfaces_already_exists() {
+    let ctx = TestContext::new();
+    let storage = ctx.storage();
+
+    ctx.with_dir(ctx.work_dir(), || {
+        add(&storage, &SilentReporter, "demo", "one", &AddOptions::default())
+            .expect("first add should succeed");
+        let err = add(&storage, &SilentReporter, "demo", "two", &AddOptions::default())
+            .expect_err("second add should fail");
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    });
+}
+
+#[test]
+#[serial]
+fn update_missing_item_surfaces_not_found() {
+    let ctx = TestContext::new();
+    let storage = ctx.storage();
+
+    ctx.with_dir(ctx.work_dir(), || {
+        let err = update(&storage, &SilentReporter, "missing", "value", None)
+            .expect_err("update should fail for missing item");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    });
+}
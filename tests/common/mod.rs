@@ -1,10 +1,13 @@
 //! Shared testing utilities mirroring the reference project's fixture culture.
 
 use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin;
+use rs_cli_tmpl::FilesystemStorage;
 use std::env;
 use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process;
 use tempfile::TempDir;
 
 /// Testing harness providing an isolated HOME/workspace pair for CLI and SDK exercises.
@@ -67,15 +70,53 @@ impl TestContext {
 
     /// Build a command for invoking the compiled `rs-cli-tmpl` binary within a custom directory.
     pub fn cli_in<P: AsRef<Path>>(&self, dir: P) -> Command {
-        let mut cmd =
-            Command::cargo_bin("rs-cli-tmpl").expect("Failed to locate rs-cli-tmpl binary");
-        cmd.current_dir(dir.as_ref()).env("HOME", self.home());
+        Command::from_std(self.process_in(dir))
+    }
+
+    /// Build a plain process command for the binary, for tests that spawn several at once.
+    pub fn process(&self) -> process::Command {
+        self.process_in(self.work_dir())
+    }
+
+    fn process_in<P: AsRef<Path>>(&self, dir: P) -> process::Command {
+        let mut cmd = process::Command::new(cargo_bin("rs-cli-tmpl"));
+        cmd.current_dir(dir.as_ref())
+            .env("HOME", self.home())
+            .env_remove("RS_CLI_TMPL_HOME")
+            .env_remove("RS_CLI_TMPL_CONFIG")
+            .env_remove("RS_CLI_TMPL_FORMAT")
+            .env_remove("RS_CLI_TMPL_CONFIRM")
+            .env_remove("RS_CLI_TMPL_COLOR")
+            .env_remove("RS_CLI_TMPL_EDITOR")
+            .env_remove("RS_CLI_TMPL_LOCK_TIMEOUT")
+            .env_remove("RS_CLI_TMPL_HISTORY_LIMIT")
+            .env_remove("RS_CLI_TMPL_ID_CHARSET")
+            .env_remove("RS_CLI_TMPL_ID_MAX_LENGTH")
+            .env_remove("RS_CLI_TMPL_ID_FOLD_CASE")
+            .env_remove("RS_CLI_TMPL_BACKEND")
+            .env_remove("XDG_DATA_HOME")
+            .env_remove("XDG_CONFIG_HOME");
         cmd
     }
 
+    /// Default storage root the CLI resolves from the emulated `$HOME`.
+    pub fn store_root(&self) -> PathBuf {
+        self.home().join(".config").join("rs-cli-tmpl")
+    }
+
+    /// Filesystem storage rooted where the CLI keeps its items, for library API exercises.
+    pub fn storage(&self) -> FilesystemStorage {
+        FilesystemStorage::new(self.store_root())
+    }
+
+    /// Path of the config file the CLI reads from the emulated `$HOME`.
+    pub fn config_path(&self) -> PathBuf {
+        self.home().join(".config").join("rs-cli-tmpl").join("config.toml")
+    }
+
     /// Return the path where the CLI stores a saved item file for the provided identifier.
     pub fn saved_item_path(&self, id: &str) -> PathBuf {
-        self.home().join(".config").join("rs-cli-tmpl").join(id).join("item.txt")
+        self.store_root().join(id).join("item.txt")
     }
 
     /// Assert that a saved item contains the provided value snippet.
@@ -0,0 +1,142 @@
+//! Builds a backend and a command outside the crate, through the public extension API only.
+
+use rs_cli_tmpl::AppError;
+use rs_cli_tmpl::commands::{restore, tag_add};
+use rs_cli_tmpl::core::filter_items::FilterItems;
+use rs_cli_tmpl::{
+    AddOptions, DeleteOptions, Execute, IdRules, ItemId, ListOptions, MemoryStorage, Storage, add,
+    delete, list,
+};
+use rs_cli_tmpl::{CaptureReporter, Event, SilentReporter};
+use rs_cli_tmpl::{ItemMetadata, TrashEntry};
+use std::cell::Cell;
+use std::io::Write;
+use std::time::Duration;
+
+/// Downstream backend counting the writes it forwards to an in-memory store.
+#[derive(Default)]
+struct CountingStorage {
+    inner: MemoryStorage,
+    writes: Cell<usize>,
+}
+
+impl CountingStorage {
+    fn write<T>(&self, result: Result<T, AppError>) -> Result<T, AppError> {
+        self.writes.set(self.writes.get() + 1);
+        result
+    }
+}
+
+impl Storage for CountingStorage {
+    fn id_rules(&self) -> IdRules {
+        IdRules { fold_case: true, ..IdRules::default() }
+    }
+
+    fn add_item(&self, id: &ItemId, content: &[u8], kind: Option<&str>) -> Result<(), AppError> {
+        self.write(self.inner.add_item(id, content, kind))
+    }
+
+    fn update_item(&self, id: &ItemId, content: &[u8], kind: Option<&str>) -> Result<(), AppError> {
+        self.write(self.inner.update_item(id, content, kind))
+    }
+
+    fn append_item(&self, id: &ItemId, content: &[u8]) -> Result<(), AppError> {
+        self.write(self.inner.append_item(id, content))
+    }
+
+    fn get_item(&self, id: &ItemId) -> Result<Vec<u8>, AppError> {
+        self.inner.get_item(id)
+    }
+
+    fn item_metadata(&self, id: &ItemId) -> Result<ItemMetadata, AppError> {
+        self.inner.item_metadata(id)
+    }
+
+    fn add_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.write(self.inner.add_tag(id, tag))
+    }
+
+    fn remove_tag(&self, id: &ItemId, tag: &str) -> Result<bool, AppError> {
+        self.write(self.inner.remove_tag(id, tag))
+    }
+
+    fn list_versions(&self, id: &ItemId) -> Result<Vec<ItemMetadata>, AppError> {
+        self.inner.list_versions(id)
+    }
+
+    fn read_version_into(
+        &self,
+        id: &ItemId,
+        version: u64,
+        writer: &mut dyn Write,
+    ) -> Result<u64, AppError> {
+        self.inner.read_version_into(id, version, writer)
+    }
+
+    fn list_items(&self) -> Result<Vec<ItemId>, AppError> {
+        self.inner.list_items()
+    }
+
+    fn delete_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(self.inner.delete_item(id))
+    }
+
+    fn purge_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(self.inner.purge_item(id))
+    }
+
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, AppError> {
+        self.inner.list_trash()
+    }
+
+    fn restore_item(&self, id: &ItemId) -> Result<(), AppError> {
+        self.write(self.inner.restore_item(id))
+    }
+
+    fn empty_trash(&self, older_than: Option<Duration>) -> Result<Vec<TrashEntry>, AppError> {
+        self.write(self.inner.empty_trash(older_than))
+    }
+}
+
+/// Downstream command built on a core command: the total size of the items carrying a tag.
+struct TaggedSize<'a> {
+    tag: &'a str,
+}
+
+impl Execute<u64> for TaggedSize<'_> {
+    fn execute(&self, storage: &impl Storage) -> Result<u64, AppError> {
+        let tags = [self.tag.to_string()];
+        let items = FilterItems { tags: &tags, match_any: false }.execute(storage)?;
+        items.iter().map(|id| Ok(storage.item_metadata(id)?.size)).sum()
+    }
+}
+
+#[test]
+fn commands_run_against_an_injected_backend() {
+    let storage = CountingStorage::default();
+    let reporter = CaptureReporter::default();
+
+    add(&storage, &reporter, "Team/Notes", "hello", &AddOptions::default()).unwrap();
+    add(&storage, &SilentReporter, "solo", "x", &AddOptions::default()).unwrap();
+    tag_add(&storage, &SilentReporter, "team/notes", "work").unwrap();
+    delete(&storage, &SilentReporter, "solo", &DeleteOptions::default()).unwrap();
+    restore(&storage, &SilentReporter, "solo").unwrap();
+
+    assert_eq!(reporter.take(), vec![Event::ItemAdded { id: "team/notes".to_string() }]);
+    let options = ListOptions { recursive: true, ..ListOptions::default() };
+    assert_eq!(list(&storage, &SilentReporter, &options).unwrap(), ["solo", "team/notes"]);
+    assert_eq!(storage.writes.get(), 5);
+    let result = add(&storage, &SilentReporter, "../escape", "x", &AddOptions::default());
+    assert!(matches!(result, Err(AppError::InvalidItemId(..))));
+}
+
+#[test]
+fn downstream_commands_compose_core_commands() {
+    let storage = MemoryStorage::new();
+    let options = AddOptions { tags: vec!["work".to_string()], ..AddOptions::default() };
+    add(&storage, &SilentReporter, "plan", "four", &options).unwrap();
+    add(&storage, &SilentReporter, "notes", "eight...", &options).unwrap();
+    add(&storage, &SilentReporter, "private", "ignored", &AddOptions::default()).unwrap();
+
+    assert_eq!(TaggedSize { tag: "work" }.execute(&storage).unwrap(), 12);
+}
@@ -0,0 +1,43 @@
+//! Runs the public conformance suite the way a downstream backend would.
+
+use rs_cli_tmpl::{FilesystemStorage, LogFileStorage, MemoryStorage};
+
+mod memory {
+    use super::*;
+
+    rs_cli_tmpl::storage_conformance_tests!(((), MemoryStorage::new()));
+}
+
+mod filesystem {
+    use super::*;
+    use tempfile::TempDir;
+
+    rs_cli_tmpl::storage_conformance_tests!({
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let storage = FilesystemStorage::new(dir.path().join("store"));
+        (dir, storage)
+    });
+}
+
+mod logfile {
+    use super::*;
+    use tempfile::TempDir;
+
+    rs_cli_tmpl::storage_conformance_tests!({
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let storage = LogFileStorage::open(dir.path().join("store.log")).unwrap();
+        (dir, storage)
+    });
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use rs_cli_tmpl::SqliteStorage;
+    use tempfile::TempDir;
+
+    rs_cli_tmpl::storage_conformance_tests!({
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let storage = SqliteStorage::open(dir.path().join("store.sqlite3")).unwrap();
+        (dir, storage)
+    });
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::TestContext;
+use rs_cli_tmpl::cli::LOG_FILE_NAME;
+use rs_cli_tmpl::commands::search;
+use rs_cli_tmpl::{AddOptions, ListOptions, SearchMode, ShowOptions, add_from_reader, list, show};
+use rs_cli_tmpl::{CaptureReporter, Event, Reporter, SilentReporter};
+use rs_cli_tmpl::{LogFileStorage, Storage};
+use serial_test::serial;
+use sha2::{Digest, Sha256};
+use std::cell::{Cell, RefCell};
+use std::io::{self, Read, Write};
+
+/// Reporter that hashes streamed content instead of keeping it.
+#[derive(Default)]
+struct DigestSink {
+    hasher: RefCell<Sha256>,
+    size: Cell<u64>,
+    non_zero: Cell<bool>,
+}
+
+impl Write for &DigestSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.borrow_mut().update(buf);
+        self.size.set(self.size.get() + buf.len() as u64);
+        if buf.iter().any(|byte| *byte != 0) {
+            self.non_zero.set(true);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Reporter for DigestSink {
+    fn report(&self, _: &Event) {}
+
+    fn content_sink(&self) -> Option<Box<dyn Write + '_>> {
+        Some(Box::new(self))
+    }
+}
+
+/// Peak resident set size of this process in KiB, where the platform exposes it.
+fn peak_rss_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Store `size` zero bytes through the streaming API, read them back and search them, checking
+/// that peak memory grows far less than the payload.
+fn assert_streams_with_bounded_memory(storage: &impl Storage, size: u64) {
+    let baseline = peak_rss_kib();
+
+    let mut stream = io::repeat(0).take(size);
+    add_from_reader(storage, &SilentReporter, "large", &mut stream, &AddOptions::default())
+        .expect("streamed add should succeed");
+
+    let sink = DigestSink::default();
+    let written =
+        show(storage, &sink, "large", &ShowOptions { raw: true, ..ShowOptions::default() })
+            .expect("streamed show should succeed");
+
+    assert_eq!(written, size);
+    assert_eq!(sink.size.get(), size);
+    assert!(!sink.non_zero.get(), "read back unexpected bytes");
+
+    let reporter = CaptureReporter::default();
+    list(storage, &reporter, &ListOptions { long: true, ..ListOptions::default() })
+        .expect("list should succeed");
+    let Some(Event::Listing { items }) = reporter.take().pop() else {
+        panic!("list should report a listing");
+    };
+    let metadata = items[0].metadata.as_ref().expect("long listing carries metadata");
+    let digest = sink.hasher.take().finalize();
+    let digest: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    assert_eq!(metadata.size, size);
+    assert_eq!(metadata.sha256, digest);
+
+    let matches = search(storage, &SilentReporter, "needle", SearchMode::default())
+        .expect("search should succeed");
+    assert!(matches.is_empty(), "binary items never match");
+
+    if let (Some(before), Some(after)) = (baseline, peak_rss_kib()) {
+        let growth = after.saturating_sub(before);
+        assert!(
+            growth < 16 * 1024,
+            "peak memory grew by {growth} KiB while streaming {size} bytes"
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn streaming_keeps_memory_bounded() {
+    let ctx = TestContext::new();
+    assert_streams_with_bounded_memory(&ctx.storage(), 40 * 1024 * 1024);
+}
+
+#[test]
+#[serial]
+fn logfile_backend_streams_with_bounded_memory() {
+    let ctx = TestContext::new();
+    let storage = LogFileStorage::open(ctx.store_root().join(LOG_FILE_NAME)).unwrap();
+    assert_streams_with_bounded_memory(&storage, 40 * 1024 * 1024);
+}
+
+#[test]
+#[serial]
+#[ignore = "writes 3 GiB to disk; run with `cargo test --release -- --ignored`"]
+fn streaming_handles_multi_gigabyte_items() {
+    let ctx = TestContext::new();
+    assert_streams_with_bounded_memory(&ctx.storage(), 3 * 1024 * 1024 * 1024);
+}